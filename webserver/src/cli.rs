@@ -2,6 +2,7 @@
 
 use clap::Parser;
 use clap::Subcommand;
+use uuid::Uuid;
 
 /// The cli
 #[derive(Parser)]
@@ -22,6 +23,13 @@ pub enum Command {
         #[clap(default_value_t = String::from("/migrations"))]
         migrations_dir: String,
     },
+    /// Grant the admin role to an account
+    ///
+    /// This is used to bootstrap the first admin, who can then manage the roles of everyone else.
+    GrantAdmin {
+        /// The uuid of the account, as shown by the `/me` endpoint
+        account: Uuid,
+    },
     /// Create new migrations
     #[cfg(debug_assertions)]
     MakeMigrations {
@@ -1,11 +1,15 @@
 //! Definitions of the configuration file
 
 use std::net::{IpAddr, Ipv4Addr};
+use std::num::NonZeroU64;
 use std::sync::LazyLock;
 
 use galvyn::core::stuff::env::{EnvError, EnvVar};
 use galvyn::rorm::DatabaseDriver;
-use openidconnect::{ClientId, ClientSecret, IssuerUrl, RedirectUrl};
+use time::{Duration, Time};
+
+use crate::utils::timezone::TimeOfDay;
+use crate::utils::timezone::Timezone;
 
 /// Load all environment variables declared in this module
 ///
@@ -16,10 +20,19 @@ pub fn load_env() -> Result<(), Vec<&'static EnvError>> {
     for result in [
         LISTEN_ADDRESS.load(),
         LISTEN_PORT.load(),
-        OIDC_DISCOVER_URL.load(),
-        OIDC_CLIENT_ID.load(),
-        OIDC_CLIENT_SECRET.load(),
-        OIDC_REDIRECT_URL.load(),
+        OIDC_PROVIDERS_FILE.load(),
+        OIDC_REFRESH_INTERVAL.load(),
+        LDAP_URL.load(),
+        LDAP_BIND_DN.load(),
+        LDAP_BIND_PASSWORD.load(),
+        LDAP_BASE_DN.load(),
+        LDAP_USER_FILTER.load(),
+        LDAP_DISPLAY_NAME_ATTRIBUTE.load(),
+        LDAP_SYNC_INTERVAL.load(),
+        LOGIN_LOG_RETENTION_DAYS.load(),
+        SESSION_LIFETIME_DAYS.load(),
+        TIMEZONE.load(),
+        DEFAULT_SIGNUP_DEADLINE.load(),
         POSTGRES_HOST.load(),
         POSTGRES_DB.load(),
         POSTGRES_PORT.load(),
@@ -43,17 +56,72 @@ pub static LISTEN_ADDRESS: EnvVar<IpAddr> =
 /// Port the API server should bind to
 pub static LISTEN_PORT: EnvVar<u16> = EnvVar::optional("LISTEN_PORT", || 8080);
 
-/// The discovery url of internal oidc
-pub static OIDC_DISCOVER_URL: EnvVar<IssuerUrl> = EnvVar::required("OIDC_DISCOVER_URL");
+/// Path to a json file listing the oidc providers users can log in with
+///
+/// See `data/oidc/providers.example.json` for the format.
+pub static OIDC_PROVIDERS_FILE: EnvVar = EnvVar::required("OIDC_PROVIDERS_FILE");
+
+/// Interval in seconds between two discoveries of the oidc providers
+///
+/// Each discovery fetches the providers' current signing keys.
+/// It must not be `0`.
+pub static OIDC_REFRESH_INTERVAL: EnvVar<NonZeroU64> =
+    EnvVar::optional("OIDC_REFRESH_INTERVAL", || {
+        NonZeroU64::new(60 * 60).unwrap_or(NonZeroU64::MIN)
+    });
+
+/// The url of the LDAP server, e.g. `ldap://glauth:3893`
+///
+/// If it is unset, LDAP is disabled and no oidc provider may require it.
+pub static LDAP_URL: EnvVar = EnvVar::optional("LDAP_URL", || "".to_string());
+
+/// The DN to bind to the LDAP server with
+///
+/// If it is unset, the server is bound to anonymously.
+pub static LDAP_BIND_DN: EnvVar = EnvVar::optional("LDAP_BIND_DN", || "".to_string());
+
+/// The password to bind to the LDAP server with
+pub static LDAP_BIND_PASSWORD: EnvVar = EnvVar::optional("LDAP_BIND_PASSWORD", || "".to_string());
+
+/// The DN below which users are searched
+pub static LDAP_BASE_DN: EnvVar = EnvVar::optional("LDAP_BASE_DN", || "".to_string());
 
-/// This server's client id for oidc
-pub static OIDC_CLIENT_ID: EnvVar<ClientId> = EnvVar::required("OIDC_CLIENT_ID");
+/// The filter to find a user by its username
+///
+/// The placeholder `{username}` is replaced by the escaped username.
+pub static LDAP_USER_FILTER: EnvVar =
+    EnvVar::optional("LDAP_USER_FILTER", || "(uid={username})".to_string());
+
+/// The attribute holding a user's display name
+pub static LDAP_DISPLAY_NAME_ATTRIBUTE: EnvVar =
+    EnvVar::optional("LDAP_DISPLAY_NAME_ATTRIBUTE", || "cn".to_string());
+
+/// Interval in seconds between two synchronisations with the LDAP server
+///
+/// It must not be `0`.
+pub static LDAP_SYNC_INTERVAL: EnvVar<NonZeroU64> = EnvVar::optional("LDAP_SYNC_INTERVAL", || {
+    NonZeroU64::new(60 * 60).unwrap_or(NonZeroU64::MIN)
+});
 
-/// This server's client secret for oidc
-pub static OIDC_CLIENT_SECRET: EnvVar<ClientSecret> = EnvVar::required("OIDC_CLIENT_SECRET");
+/// Number of days login attempts are kept
+pub static LOGIN_LOG_RETENTION_DAYS: EnvVar<u32> =
+    EnvVar::optional("LOGIN_LOG_RETENTION_DAYS", || 90);
 
-/// The url the oidc servers should redirect the user to
-pub static OIDC_REDIRECT_URL: EnvVar<RedirectUrl> = EnvVar::required("OIDC_REDIRECT_URL");
+/// Number of days a login is kept after it was last used
+///
+/// Logins which were not used for longer are logged out and deleted.
+pub static SESSION_LIFETIME_DAYS: EnvVar<u32> = EnvVar::optional("SESSION_LIFETIME_DAYS", || 30);
+
+/// IANA name of the timezone dinners take place in, e.g. `Europe/Berlin`
+pub static TIMEZONE: EnvVar<Timezone> = EnvVar::optional("TIMEZONE", Timezone::default);
+
+/// Time of the day (`HH:MM`) sign-ups for a dinner close on the dinner's day
+///
+/// Cooks may set another deadline for each dinner.
+pub static DEFAULT_SIGNUP_DEADLINE: EnvVar<TimeOfDay> =
+    EnvVar::optional("DEFAULT_SIGNUP_DEADLINE", || {
+        TimeOfDay(Time::MIDNIGHT + Duration::hours(10))
+    });
 
 /// The address of the database server
 pub static POSTGRES_HOST: EnvVar = EnvVar::optional("POSTGRES_HOST", || "postgres".to_string());
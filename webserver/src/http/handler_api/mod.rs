@@ -0,0 +1,36 @@
+//! Parts of the http api for scripts and integrations
+//!
+//! Requests are authenticated by API tokens instead of a session.
+//! Handlers are shared with the frontend's api where possible.
+//! Tokens with the [`ReadOnly`](crate::models::api_tokens::ApiTokenScope::ReadOnly) scope
+//! are limited to `GET` requests by the [`ApiTokenRequiredLayer`].
+
+use galvyn::core::GalvynRouter;
+use galvyn::openapi::OpenapiRouterExt;
+
+use crate::http::handler_frontend::accounts;
+use crate::http::handler_frontend::dinners;
+use crate::http::middlewares::api_token_required::ApiTokenRequiredLayer;
+
+/// Initialize the routes of the api
+pub fn initialize_routes() -> GalvynRouter {
+    GalvynRouter::new()
+        .nest(
+            "/accounts",
+            GalvynRouter::new()
+                .openapi_tag("Accounts")
+                .handler(accounts::handler::get_me),
+        )
+        .nest(
+            "/dinners",
+            GalvynRouter::new()
+                .openapi_tag("Dinners")
+                .handler(dinners::handler::get_all_dinners)
+                .handler(dinners::handler::get_dinner_calendar)
+                .handler(dinners::handler::get_dinner)
+                .handler(dinners::handler::join_dinner)
+                .handler(dinners::handler::change_dinner_signup)
+                .handler(dinners::handler::leave_dinner),
+        )
+        .wrap(ApiTokenRequiredLayer)
+}
@@ -0,0 +1,344 @@
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::core::stuff::schema::List;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::put;
+use galvyn::rorm::Database;
+use tracing::info;
+
+use crate::http::handler_frontend::accounts::schema::AccountSessionPath;
+use crate::http::handler_frontend::accounts::schema::AdjustBalanceRequest;
+use crate::http::handler_frontend::accounts::schema::DeactivateAccountRequest;
+use crate::http::handler_frontend::accounts::schema::FullAccount;
+use crate::http::handler_frontend::accounts::schema::ImpersonationLogEntry;
+use crate::http::handler_frontend::accounts::schema::SetAccountRolesRequest;
+use crate::http::handler_frontend::sessions::schema::SimpleAccountSession;
+use crate::models::account_sessions::AccountSession;
+use crate::models::accounts::Account;
+use crate::models::accounts::AccountRole;
+use crate::models::impersonation_logs::IMPERSONATION_STARTED;
+use crate::models::impersonation_logs::ImpersonationLog;
+use crate::modules::event_bus::EventBus;
+use crate::modules::event_bus::EventRecipient;
+use crate::modules::event_bus::WsMessage;
+
+/// Retrieve the currently logged-in account
+#[get("/me")]
+pub async fn get_me(account: Account, session: Session) -> ApiResult<ApiJson<FullAccount>> {
+    let roles = account.get_roles(Database::global()).await?;
+    let impersonation = Account::get_impersonation(&session).await?;
+    Ok(ApiJson(FullAccount {
+        uuid: account.uuid,
+        display_name: account.display_name.to_string(),
+        email: account.email.map(|email| email.to_string()),
+        balance: account.balance,
+        roles,
+        deactivated_at: account.deactivated_at,
+        sponsor: account.sponsor,
+        impersonated_by: impersonation.map(|(admin, _)| admin),
+    }))
+}
+
+/// Retrieve all accounts
+///
+/// Requires the `Admin` role.
+#[get("/")]
+pub async fn get_all_accounts() -> ApiResult<ApiJson<List<FullAccount>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut roles = Account::get_all_roles(&mut tx).await?;
+    let list = Account::find_all(&mut tx)
+        .await?
+        .into_iter()
+        .map(|account| FullAccount {
+            uuid: account.uuid,
+            display_name: account.display_name.to_string(),
+            email: account.email.map(|email| email.to_string()),
+            balance: account.balance,
+            roles: roles.remove(&account.uuid).unwrap_or_default(),
+            deactivated_at: account.deactivated_at,
+            sponsor: account.sponsor,
+            impersonated_by: None,
+        })
+        .collect();
+
+    tx.commit().await?;
+    Ok(ApiJson(List { list }))
+}
+
+/// Replace the roles granted to an account
+///
+/// Requires the `Admin` role.
+#[put("/{uuid}/roles")]
+pub async fn set_account_roles(
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<SetAccountRolesRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    account.set_roles(&mut tx, &request.roles).await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Retrieve the active sessions of an account
+///
+/// Requires the `Admin` role.
+#[get("/{uuid}/sessions")]
+pub async fn get_account_sessions(
+    session: Session,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<ApiJson<List<SimpleAccountSession>>> {
+    let current = Account::get_account_session(&session).await?;
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    let list = AccountSession::find_all_by_account(&mut tx, account.uuid)
+        .await?
+        .into_iter()
+        .map(|account_session| SimpleAccountSession::new(account_session, current))
+        .collect();
+
+    tx.commit().await?;
+    Ok(ApiJson(List { list }))
+}
+
+/// Revoke a session of an account
+///
+/// Requires the `Admin` role.
+#[delete("/{uuid}/sessions/{session_uuid}")]
+pub async fn revoke_account_session(
+    Path(AccountSessionPath { uuid, session_uuid }): Path<AccountSessionPath>,
+) -> ApiResult<()> {
+    if !AccountSession::delete(Database::global(), uuid, session_uuid).await? {
+        return Err(ApiError::bad_request("Session does not exist"));
+    }
+    EventBus::global().publish_logged_out([session_uuid]);
+    Ok(())
+}
+
+/// Revoke all sessions of an account
+///
+/// Requires the `Admin` role.
+#[delete("/{uuid}/sessions")]
+pub async fn revoke_account_sessions(Path(SingleUuid { uuid }): Path<SingleUuid>) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    let revoked = AccountSession::delete_all_by_account(&mut tx, account.uuid, None).await?;
+
+    tx.commit().await?;
+
+    EventBus::global().publish_logged_out(revoked);
+    Ok(())
+}
+
+/// Deactivate an account
+///
+/// Deactivated accounts can't log in anymore but are kept to preserve their history.
+/// The account's balance has to be settled beforehand unless it is written off.
+///
+/// Requires the `Admin` role.
+#[post("/{uuid}/deactivate")]
+pub async fn deactivate_account(
+    current: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<DeactivateAccountRequest>,
+) -> ApiResult<()> {
+    if uuid == current.uuid {
+        return Err(ApiError::bad_request(
+            "You can't deactivate your own account",
+        ));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    // The balance must not change between checking and deactivating
+    Account::lock(&mut tx, uuid).await?;
+    let mut account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    if account.is_deactivated() {
+        return Err(ApiError::bad_request("Account is already deactivated"));
+    }
+
+    if account.balance != 0 {
+        if !request.write_off_balance {
+            return Err(ApiError::bad_request(
+                "The account's balance has to be settled first",
+            ));
+        }
+        if !current.has_role(&mut tx, AccountRole::Treasurer).await? {
+            return Err(ApiError::new(
+                ApiStatusCode::MissingPrivileges,
+                "Writing off a balance requires the Treasurer role",
+            ));
+        }
+        let amount = account.write_off_balance(&mut tx, current.uuid).await?;
+        info!(account.uuid = %account.uuid, amount, treasurer = %current.uuid, "Wrote off balance");
+    }
+
+    let ended = account.deactivate(&mut tx).await?;
+
+    tx.commit().await?;
+
+    EventBus::global().publish_logged_out(ended);
+    Ok(())
+}
+
+/// Reactivate a deactivated account
+///
+/// Requires the `Admin` role.
+#[post("/{uuid}/reactivate")]
+pub async fn reactivate_account(Path(SingleUuid { uuid }): Path<SingleUuid>) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    if !account.is_deactivated() {
+        return Err(ApiError::bad_request("Account is not deactivated"));
+    }
+    account.reactivate(&mut tx).await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Start impersonating an account
+///
+/// Until the impersonation is stopped, all requests of the logged-in admin are performed as the
+/// impersonated account and recorded in the impersonation log.
+///
+/// Requires the `Admin` role.
+#[post("/{uuid}/impersonate")]
+pub async fn impersonate_account(
+    current: Account,
+    session: Session,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    if uuid == current.uuid {
+        return Err(ApiError::bad_request("You can't impersonate yourself"));
+    }
+    if Account::get_impersonation(&session).await?.is_some() {
+        return Err(ApiError::bad_request(
+            "You are already impersonating an account",
+        ));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    if account.is_deactivated() {
+        return Err(ApiError::bad_request("Account is deactivated"));
+    }
+    ImpersonationLog::create(
+        &mut tx,
+        current.uuid,
+        account.uuid,
+        IMPERSONATION_STARTED,
+        "",
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Account::start_impersonation(&session, account.uuid).await?;
+    info!(account.uuid = %account.uuid, admin = %current.uuid, "Started impersonation");
+    Ok(())
+}
+
+/// Retrieve the impersonation log entries an account is involved in
+///
+/// Requires the `Admin` role.
+#[get("/{uuid}/impersonation-log")]
+pub async fn get_impersonation_log(
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<ApiJson<List<ImpersonationLogEntry>>> {
+    let list = ImpersonationLog::find_all_by_account(Database::global(), uuid)
+        .await?
+        .into_iter()
+        .map(|entry| ImpersonationLogEntry {
+            uuid: entry.uuid,
+            admin: entry.admin,
+            account: entry.account,
+            method: entry.method.to_string(),
+            path: entry.path.to_string(),
+            created_at: entry.created_at,
+        })
+        .collect();
+    Ok(ApiJson(List { list }))
+}
+
+/// Stop impersonating an account
+///
+/// This is available to the impersonating admin regardless of the impersonated account's roles.
+#[delete("/impersonation")]
+pub async fn stop_impersonation(session: Session) -> ApiResult<()> {
+    let Some((admin, account)) = Account::get_impersonation(&session).await? else {
+        return Err(ApiError::bad_request(
+            "You are not impersonating an account",
+        ));
+    };
+    Account::stop_impersonation(&session).await?;
+
+    info!(account.uuid = %account, admin = %admin, "Stopped impersonation");
+    Ok(())
+}
+
+/// Adjust the balance of an account
+///
+/// Charges (negative amounts) of guests billing their sponsor are applied to the sponsor's balance.
+/// Deposits are always applied to the account itself.
+///
+/// Requires the `Treasurer` role.
+#[post("/{uuid}/balance")]
+pub async fn adjust_account_balance(
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<AdjustBalanceRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    let mut account = if request.amount >= 0 || account.billed_account() == account.uuid {
+        account
+    } else {
+        Account::find_by_uuid(&mut tx, account.billed_account())
+            .await?
+            .ok_or(ApiError::server_error("Sponsor does not exist"))?
+    };
+    if account.uuid != uuid && account.is_deactivated() {
+        return Err(ApiError::bad_request("The guest's sponsor is deactivated"));
+    }
+    account.adjust_balance(&mut tx, request.amount).await?;
+
+    tx.commit().await?;
+
+    EventBus::global().publish(
+        EventRecipient::Account(account.uuid),
+        WsMessage::BalanceChanged {
+            balance: account.balance,
+        },
+    );
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::AccountRole;
+
+/// The full representation of an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FullAccount {
+    /// The account's primary key
+    pub uuid: Uuid,
+
+    /// The name that is used for displaying purposes
+    pub display_name: String,
+
+    /// The email address provided by the oidc provider
+    pub email: Option<String>,
+
+    /// Current balance of the account (i.e., what it owes to the community)
+    pub balance: i64,
+
+    /// The roles granted to the account
+    pub roles: Vec<AccountRole>,
+
+    /// The point in time the account was deactivated
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub deactivated_at: Option<OffsetDateTime>,
+
+    /// The member sponsoring the account if it is a guest
+    pub sponsor: Option<Uuid>,
+
+    /// The admin impersonating the account
+    ///
+    /// This is only set by `/me` while the logged-in admin impersonates the account.
+    pub impersonated_by: Option<Uuid>,
+}
+
+/// The public representation of an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimpleAccount {
+    /// The account's primary key
+    pub uuid: Uuid,
+
+    /// The name that is used for displaying purposes
+    pub display_name: String,
+}
+
+/// The request to replace an account's roles
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetAccountRolesRequest {
+    /// The roles to grant
+    pub roles: Vec<AccountRole>,
+}
+
+/// The request to adjust an account's balance
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AdjustBalanceRequest {
+    /// The amount to add to the balance
+    ///
+    /// Positive values increase what the account owes to the community.
+    pub amount: i64,
+}
+
+/// The request to deactivate an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeactivateAccountRequest {
+    /// Write off the account's outstanding balance instead of requiring it to be settled
+    ///
+    /// This requires the `Treasurer` role.
+    #[serde(default)]
+    pub write_off_balance: bool,
+}
+
+/// A request performed by an admin while impersonating an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImpersonationLogEntry {
+    /// The entry's primary key
+    pub uuid: Uuid,
+
+    /// The impersonating admin
+    ///
+    /// This is `None` if the admin's account has been deleted since.
+    pub admin: Option<Uuid>,
+
+    /// The impersonated account
+    ///
+    /// This is `None` if the account has been deleted since.
+    pub account: Option<Uuid>,
+
+    /// The http method of the request
+    pub method: String,
+
+    /// The path of the request
+    pub path: String,
+
+    /// The point in time the request was performed
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Path identifying a session of an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccountSessionPath {
+    /// The account's primary key
+    pub uuid: Uuid,
+
+    /// The session's primary key
+    pub session_uuid: Uuid,
+}
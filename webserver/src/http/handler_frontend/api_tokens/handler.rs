@@ -0,0 +1,102 @@
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::core::stuff::schema::List;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use galvyn::rorm::fields::types::MaxStr;
+use time::OffsetDateTime;
+
+use crate::http::handler_frontend::api_tokens::schema::CreateApiTokenRequest;
+use crate::http::handler_frontend::api_tokens::schema::CreateApiTokenResponse;
+use crate::http::handler_frontend::api_tokens::schema::SimpleApiToken;
+use crate::models::accounts::Account;
+use crate::models::api_tokens::ApiToken;
+
+/// Retrieve the API tokens of the logged-in account
+#[get("/")]
+pub async fn get_api_tokens(account: Account) -> ApiResult<ApiJson<List<SimpleApiToken>>> {
+    let list = ApiToken::find_all_by_account(Database::global(), account.uuid)
+        .await?
+        .into_iter()
+        .map(|token| SimpleApiToken {
+            uuid: token.uuid,
+            name: token.name.to_string(),
+            scope: token.scope,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+        })
+        .collect();
+    Ok(ApiJson(List { list }))
+}
+
+/// Create a new API token for the logged-in account
+///
+/// The returned token can't be retrieved again.
+/// Admins impersonating an account can't create tokens for it.
+#[post("/")]
+pub async fn create_api_token(
+    account: Account,
+    session: Session,
+    ApiJson(request): ApiJson<CreateApiTokenRequest>,
+) -> ApiResult<ApiJson<CreateApiTokenResponse>> {
+    reject_impersonation(&session).await?;
+
+    let name = MaxStr::new(request.name).map_err(|_| ApiError::bad_request("Name is too long"))?;
+    if request
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+    {
+        return Err(ApiError::bad_request("Expiry must be in the future"));
+    }
+
+    let (token, secret) = ApiToken::create(
+        Database::global(),
+        account.uuid,
+        name,
+        request.scope,
+        request.expires_at,
+    )
+    .await?;
+
+    Ok(ApiJson(CreateApiTokenResponse {
+        uuid: token.uuid,
+        token: secret,
+    }))
+}
+
+/// Revoke an API token of the logged-in account
+///
+/// Admins impersonating an account can't revoke its tokens.
+#[delete("/{uuid}")]
+pub async fn delete_api_token(
+    account: Account,
+    session: Session,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    reject_impersonation(&session).await?;
+
+    if !ApiToken::delete(Database::global(), account.uuid, uuid).await? {
+        return Err(ApiError::bad_request("Api token does not exist"));
+    }
+    Ok(())
+}
+
+/// Rejects managing the tokens of an impersonated account
+async fn reject_impersonation(session: &Session) -> ApiResult<()> {
+    if Account::get_impersonation(session).await?.is_some() {
+        return Err(ApiError::new(
+            ApiStatusCode::MissingPrivileges,
+            "API tokens can't be managed while impersonating an account",
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::api_tokens::ApiTokenScope;
+
+/// The request to create a new API token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateApiTokenRequest {
+    /// A name to recognize the token
+    pub name: String,
+
+    /// What the token is allowed to do
+    pub scope: ApiTokenScope,
+
+    /// The point in time after which the token is no longer valid
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+/// A newly created API token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateApiTokenResponse {
+    /// The token's primary key
+    pub uuid: Uuid,
+
+    /// The token to send as `Authorization: Bearer <token>`
+    ///
+    /// It can't be retrieved again.
+    pub token: String,
+}
+
+/// An API token without its secret
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimpleApiToken {
+    /// The token's primary key
+    pub uuid: Uuid,
+
+    /// A name to recognize the token
+    pub name: String,
+
+    /// What the token is allowed to do
+    pub scope: ApiTokenScope,
+
+    /// The point in time the token was created
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub created_at: OffsetDateTime,
+
+    /// The point in time after which the token is no longer valid
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub expires_at: Option<OffsetDateTime>,
+
+    /// The point in time the token was last used
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub last_used_at: Option<OffsetDateTime>,
+}
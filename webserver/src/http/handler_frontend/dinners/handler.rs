@@ -0,0 +1,720 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::re_exports::axum::extract::Query;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::core::stuff::schema::List;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::put;
+use galvyn::rorm::Database;
+use galvyn::rorm::db::transaction::Transaction;
+use galvyn::rorm::fields::types::MaxStr;
+use time::Date;
+use time::Duration;
+use time::Weekday;
+use time_tz::TimeZone;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::config::TIMEZONE;
+use crate::http::handler_frontend::accounts::schema::SimpleAccount;
+use crate::http::handler_frontend::dinners::schema::CreateDinnerRequest;
+use crate::http::handler_frontend::dinners::schema::DinnerAttendee;
+use crate::http::handler_frontend::dinners::schema::DinnerCalendar;
+use crate::http::handler_frontend::dinners::schema::DinnerCalendarDay;
+use crate::http::handler_frontend::dinners::schema::DinnerCalendarEntry;
+use crate::http::handler_frontend::dinners::schema::DinnerSignupPath;
+use crate::http::handler_frontend::dinners::schema::DinnerSignupRequest;
+use crate::http::handler_frontend::dinners::schema::DinnerSignupResponse;
+use crate::http::handler_frontend::dinners::schema::FullDinner;
+use crate::http::handler_frontend::dinners::schema::GetAllDinnersRequest;
+use crate::http::handler_frontend::dinners::schema::GetDinnerCalendarRequest;
+use crate::http::handler_frontend::dinners::schema::RemoveDinnerSignupRequest;
+use crate::http::handler_frontend::dinners::schema::UpdateDinnerRequest;
+use crate::models::accounts::Account;
+use crate::models::accounts::AccountRole;
+use crate::models::dinner_signups::DinnerSignup;
+use crate::models::dinner_signups::MAX_PORTIONS;
+use crate::models::dinners::Dinner;
+use crate::models::dinners::DinnerState;
+use crate::models::dinners::DinnerUpdate;
+use crate::modules::event_bus::EventBus;
+use crate::modules::event_bus::EventRecipient;
+use crate::modules::event_bus::WsMessage;
+
+/// Maximum number of dinners retrieved by a single request
+const MAX_LIMIT: u64 = 100;
+
+/// Retrieve a page of the dinners ordered by their date
+#[get("/")]
+pub async fn get_all_dinners(
+    Query(request): Query<GetAllDinnersRequest>,
+) -> ApiResult<ApiJson<List<FullDinner>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinners = Dinner::find_page(
+        &mut tx,
+        request.limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT),
+        request.offset,
+    )
+    .await?;
+    let list = full_dinners(&mut tx, dinners).await?;
+
+    tx.commit().await?;
+    Ok(ApiJson(List { list }))
+}
+
+/// Maximum number of days a single calendar request may span
+const MAX_CALENDAR_DAYS: i64 = 42;
+
+/// Retrieve the dinners of an ISO week or a range of days grouped by day
+///
+/// Without parameters, the current week in the server's timezone is returned.
+#[get("/calendar")]
+pub async fn get_dinner_calendar(
+    account: Account,
+    Query(request): Query<GetDinnerCalendarRequest>,
+) -> ApiResult<ApiJson<DinnerCalendar>> {
+    let timezone = *TIMEZONE.get();
+    let today = timezone.today();
+
+    let (from, to) = match request {
+        GetDinnerCalendarRequest {
+            year: None,
+            week: None,
+            from: Some(from),
+            to: Some(to),
+        } => {
+            if to < from {
+                return Err(ApiError::bad_request("to must not be before from"));
+            }
+            if (to - from).whole_days() >= MAX_CALENDAR_DAYS {
+                return Err(ApiError::bad_request("The range is too long"));
+            }
+            (from, to)
+        }
+        GetDinnerCalendarRequest {
+            year: Some(year),
+            week: Some(week),
+            from: None,
+            to: None,
+        } => {
+            let monday = Date::from_iso_week_date(year, week, Weekday::Monday)
+                .map_err(|_| ApiError::bad_request("Invalid week"))?;
+            let sunday = monday
+                .checked_add(Duration::days(6))
+                .ok_or(ApiError::bad_request("Invalid week"))?;
+            (monday, sunday)
+        }
+        GetDinnerCalendarRequest {
+            year: None,
+            week: None,
+            from: None,
+            to: None,
+        } => {
+            let monday = today - Duration::days(today.weekday().number_days_from_monday().into());
+            (monday, monday + Duration::days(6))
+        }
+        _ => {
+            return Err(ApiError::bad_request(
+                "Either year and week or from and to have to be set",
+            ));
+        }
+    };
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinners = Dinner::find_by_date_range(&mut tx, from, to).await?;
+    let mut dinners = full_dinners(&mut tx, dinners).await?.into_iter().peekable();
+
+    tx.commit().await?;
+
+    let mut days = Vec::new();
+    let mut date = from;
+    loop {
+        let mut day = DinnerCalendarDay {
+            date,
+            signups: 0,
+            portions: 0,
+            dinners: Vec::new(),
+        };
+        while let Some(dinner) = dinners.next_if(|dinner| dinner.date == date) {
+            day.signups += dinner
+                .attendees
+                .iter()
+                .filter(|attendee| attendee.cancelled_at.is_none())
+                .count();
+            day.portions += dinner.portions;
+            day.dinners.push(DinnerCalendarEntry {
+                my_portions: dinner
+                    .attendees
+                    .iter()
+                    .find(|attendee| {
+                        attendee.account.uuid == account.uuid && attendee.cancelled_at.is_none()
+                    })
+                    .map(|attendee| attendee.portions),
+                my_waitlist_position: dinner
+                    .waitlist
+                    .iter()
+                    .position(|attendee| attendee.account.uuid == account.uuid)
+                    .map(|position| position + 1),
+                dinner,
+            });
+        }
+        days.push(day);
+        match date.next_day() {
+            Some(next) if next <= to => date = next,
+            _ => break,
+        }
+    }
+
+    Ok(ApiJson(DinnerCalendar {
+        from,
+        to,
+        today,
+        timezone: timezone.0.name().to_string(),
+        days,
+    }))
+}
+
+/// Retrieve a single dinner
+#[get("/{uuid}")]
+pub async fn get_dinner(
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<ApiJson<FullDinner>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = Dinner::find_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Dinner does not exist"))?;
+    let dinner = full_dinners(&mut tx, vec![dinner])
+        .await?
+        .pop()
+        .ok_or(ApiError::server_error("Dinner vanished"))?;
+
+    tx.commit().await?;
+    Ok(ApiJson(dinner))
+}
+
+/// Create a dinner
+///
+/// Requires the `Cook` role.
+#[post("/")]
+pub async fn create_dinner(
+    account: Account,
+    ApiJson(request): ApiJson<CreateDinnerRequest>,
+) -> ApiResult<ApiJson<SingleUuid>> {
+    let (title, description) = parse_details(request.title, request.description)?;
+    check_capacity(request.capacity)?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let cook = request.cook.unwrap_or(account.uuid);
+    check_cook(&mut tx, &account, cook).await?;
+    let dinner = Dinner::create(
+        &mut tx,
+        request.date,
+        title,
+        description,
+        cook,
+        request.signup_deadline,
+        request.capacity,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(dinner.uuid = %dinner.uuid, account.uuid = %account.uuid, "Created dinner");
+    Ok(ApiJson(SingleUuid { uuid: dinner.uuid }))
+}
+
+/// Change a dinner
+///
+/// Only the dinner's cook or an admin may change it.
+#[put("/{uuid}")]
+pub async fn update_dinner(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<UpdateDinnerRequest>,
+) -> ApiResult<()> {
+    let (title, description) = parse_details(request.title, request.description)?;
+    check_capacity(request.capacity)?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut dinner = find_managed_dinner(&mut tx, &account, uuid).await?;
+    if request.cook != dinner.cook {
+        check_cook(&mut tx, &account, request.cook).await?;
+    }
+    dinner
+        .update(
+            &mut tx,
+            DinnerUpdate {
+                date: request.date,
+                title,
+                description,
+                cook: request.cook,
+                state: request.state,
+                signup_deadline: request.signup_deadline,
+                capacity: request.capacity,
+            },
+        )
+        .await?;
+    let promoted = promote_waitlist(&mut tx, &dinner).await?;
+
+    tx.commit().await?;
+
+    publish_signups_changed(uuid, promoted);
+    Ok(())
+}
+
+/// Delete a dinner
+///
+/// Only the dinner's cook or an admin may delete it.
+#[delete("/{uuid}")]
+pub async fn delete_dinner(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = find_managed_dinner(&mut tx, &account, uuid).await?;
+    dinner.delete(&mut tx).await?;
+
+    tx.commit().await?;
+
+    info!(dinner.uuid = %uuid, account.uuid = %account.uuid, "Deleted dinner");
+    Ok(())
+}
+
+/// Sign the logged-in account up for a dinner
+///
+/// The portions include the ones for the account's guests.
+/// If the dinner is full or others are waiting already, the sign-up is put on the waitlist.
+/// After the sign-up deadline, only the cook may add attendees.
+#[post("/{uuid}/signup")]
+pub async fn join_dinner(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<DinnerSignupRequest>,
+) -> ApiResult<ApiJson<DinnerSignupResponse>> {
+    check_portions(request.portions)?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = find_open_dinner(&mut tx, uuid).await?;
+    if dinner.signups_closed() {
+        return Err(ApiError::bad_request(
+            "Sign-ups are closed, ask the cook to add you",
+        ));
+    }
+    let signups = DinnerSignup::find_all_by_dinner(&mut tx, uuid).await?;
+    if signups
+        .iter()
+        .any(|signup| signup.account == account.uuid && !signup.is_cancelled())
+    {
+        return Err(ApiError::bad_request("Already signed up for the dinner"));
+    }
+    let waitlisted = dinner.capacity.is_some_and(|capacity| {
+        signups.iter().any(|signup| signup.waitlisted)
+            || DinnerSignup::seats_taken(&signups) + request.portions > capacity
+    });
+    set_signup(&mut tx, uuid, account.uuid, request.portions, waitlisted).await?;
+
+    tx.commit().await?;
+
+    publish_signups_changed(uuid, Vec::new());
+    Ok(ApiJson(DinnerSignupResponse { waitlisted }))
+}
+
+/// Change the number of portions the logged-in account signed up for
+///
+/// Additional portions have to fit into the dinner's capacity unless the sign-up is waitlisted.
+/// After the sign-up deadline, only the cook may change sign-ups.
+#[put("/{uuid}/signup")]
+pub async fn change_dinner_signup(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<DinnerSignupRequest>,
+) -> ApiResult<()> {
+    check_portions(request.portions)?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = find_open_dinner(&mut tx, uuid).await?;
+    if dinner.signups_closed() {
+        return Err(ApiError::bad_request(
+            "Sign-ups are closed, ask the cook to change yours",
+        ));
+    }
+    let mut signup = find_active_signup(&mut tx, uuid, account.uuid).await?;
+    if let Some(capacity) = dinner.capacity.filter(|_| !signup.waitlisted) {
+        let signups = DinnerSignup::find_all_by_dinner(&mut tx, uuid).await?;
+        let seats = DinnerSignup::seats_taken(&signups) - signup.portions + request.portions;
+        if request.portions > signup.portions && seats > capacity {
+            return Err(ApiError::bad_request("Not enough free seats"));
+        }
+    }
+    signup.set_portions(&mut tx, request.portions).await?;
+    let promoted = promote_waitlist(&mut tx, &dinner).await?;
+
+    tx.commit().await?;
+
+    publish_signups_changed(uuid, promoted);
+    Ok(())
+}
+
+/// Withdraw the logged-in account's sign-up for a dinner
+///
+/// After the sign-up deadline, the sign-up is kept as late cancellation which may still be charged.
+/// Waitlisted sign-ups are always withdrawn without charge.
+/// The freed seats are given to the waitlist.
+#[delete("/{uuid}/signup")]
+pub async fn leave_dinner(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = find_open_dinner(&mut tx, uuid).await?;
+    let mut signup = find_active_signup(&mut tx, uuid, account.uuid).await?;
+    if dinner.signups_closed() && !signup.waitlisted {
+        signup.cancel(&mut tx).await?;
+        info!(dinner.uuid = %uuid, account.uuid = %account.uuid, "Late cancellation");
+    } else {
+        signup.delete(&mut tx).await?;
+    }
+    let promoted = promote_waitlist(&mut tx, &dinner).await?;
+
+    tx.commit().await?;
+
+    publish_signups_changed(uuid, promoted);
+    Ok(())
+}
+
+/// Sign an account up for a dinner or change its sign-up
+///
+/// This is how the cook approves sign-ups and changes after the sign-up deadline.
+/// Sign-ups set by the cook are taken off the waitlist and may exceed the dinner's capacity.
+/// Late cancellations of the account are reverted.
+///
+/// Only the dinner's cook or an admin may change sign-ups of other accounts.
+#[put("/{uuid}/signups/{account}")]
+pub async fn set_attendee_signup(
+    account: Account,
+    Path(DinnerSignupPath {
+        uuid,
+        account: attendee,
+    }): Path<DinnerSignupPath>,
+    ApiJson(request): ApiJson<DinnerSignupRequest>,
+) -> ApiResult<()> {
+    check_portions(request.portions)?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = find_managed_dinner(&mut tx, &account, uuid).await?;
+    if dinner.state != DinnerState::Planned {
+        return Err(ApiError::bad_request("Dinner is no longer planned"));
+    }
+    Account::find_by_uuid(&mut tx, attendee)
+        .await?
+        .filter(|attendee| !attendee.is_deactivated())
+        .ok_or(ApiError::bad_request("Account does not exist"))?;
+    set_signup(&mut tx, uuid, attendee, request.portions, false).await?;
+    let promoted = promote_waitlist(&mut tx, &dinner).await?;
+
+    tx.commit().await?;
+
+    info!(dinner.uuid = %uuid, account.uuid = %attendee, cook = %account.uuid, "Cook set sign-up");
+    publish_signups_changed(uuid, promoted);
+    Ok(())
+}
+
+/// Remove the sign-up of an account
+///
+/// With `charge`, the sign-up is kept as late cancellation which may still be charged.
+/// Otherwise, it is deleted, which waives the charge of a late cancellation as well.
+/// The freed seats are given to the waitlist.
+///
+/// Only the dinner's cook or an admin may remove sign-ups of other accounts.
+#[delete("/{uuid}/signups/{account}")]
+pub async fn remove_attendee_signup(
+    account: Account,
+    Path(DinnerSignupPath {
+        uuid,
+        account: attendee,
+    }): Path<DinnerSignupPath>,
+    Query(request): Query<RemoveDinnerSignupRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let dinner = find_managed_dinner(&mut tx, &account, uuid).await?;
+    let mut signup = DinnerSignup::find_by_account(&mut tx, uuid, attendee)
+        .await?
+        .ok_or(ApiError::bad_request("Account is not signed up"))?;
+    if !request.charge {
+        signup.delete(&mut tx).await?;
+    } else if signup.is_cancelled() {
+        return Err(ApiError::bad_request("Sign-up is already cancelled"));
+    } else {
+        signup.cancel(&mut tx).await?;
+    }
+    let promoted = promote_waitlist(&mut tx, &dinner).await?;
+
+    tx.commit().await?;
+
+    info!(
+        dinner.uuid = %uuid,
+        account.uuid = %attendee,
+        cook = %account.uuid,
+        charge = request.charge,
+        "Cook removed sign-up"
+    );
+    publish_signups_changed(uuid, promoted);
+    Ok(())
+}
+
+/// Validates the number of portions of a sign-up
+fn check_portions(portions: i32) -> ApiResult<()> {
+    if !(1..=MAX_PORTIONS).contains(&portions) {
+        return Err(ApiError::bad_request(
+            "Portions have to be between 1 and 10",
+        ));
+    }
+    Ok(())
+}
+
+/// Find a dinner whose sign-ups may still be changed
+///
+/// The dinner is locked until the transaction ends, so its sign-ups can be counted safely.
+async fn find_open_dinner(tx: &mut Transaction, uuid: Uuid) -> ApiResult<Dinner> {
+    Dinner::lock(&mut *tx, uuid).await?;
+    let dinner = Dinner::find_by_uuid(&mut *tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Dinner does not exist"))?;
+    if dinner.state != DinnerState::Planned {
+        return Err(ApiError::bad_request("Dinner is no longer planned"));
+    }
+    Ok(dinner)
+}
+
+/// Find the sign-up of an account for a dinner unless it was cancelled
+async fn find_active_signup(
+    tx: &mut Transaction,
+    dinner: Uuid,
+    account: Uuid,
+) -> ApiResult<DinnerSignup> {
+    DinnerSignup::find_by_account(&mut *tx, dinner, account)
+        .await?
+        .filter(|signup| !signup.is_cancelled())
+        .ok_or(ApiError::bad_request("Not signed up for the dinner"))
+}
+
+/// Signs an account up for a dinner or changes its existing sign-up
+///
+/// A late cancellation is reverted.
+async fn set_signup(
+    tx: &mut Transaction,
+    dinner: Uuid,
+    account: Uuid,
+    portions: i32,
+    waitlisted: bool,
+) -> ApiResult<()> {
+    match DinnerSignup::find_by_account(&mut *tx, dinner, account).await? {
+        Some(mut signup) => {
+            if signup.is_cancelled() {
+                signup.restore(&mut *tx).await?;
+            }
+            if signup.waitlisted != waitlisted {
+                signup.set_waitlisted(&mut *tx, waitlisted).await?;
+            }
+            signup.set_portions(&mut *tx, portions).await?;
+        }
+        None => {
+            DinnerSignup::create(&mut *tx, dinner, account, portions, waitlisted)
+                .await?
+                .ok_or(ApiError::bad_request("Already signed up for the dinner"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Gives free seats of a planned dinner to its waitlist
+async fn promote_waitlist(tx: &mut Transaction, dinner: &Dinner) -> ApiResult<Vec<DinnerSignup>> {
+    if dinner.state != DinnerState::Planned {
+        return Ok(Vec::new());
+    }
+    let promoted = DinnerSignup::promote_waitlist(&mut *tx, dinner.uuid, dinner.capacity).await?;
+    for signup in &promoted {
+        info!(dinner.uuid = %dinner.uuid, account.uuid = %signup.account, "Promoted from waitlist");
+    }
+    Ok(promoted)
+}
+
+/// Notifies all websockets about changed sign-ups of a dinner
+///
+/// Accounts whose sign-ups were taken off the waitlist are notified separately.
+fn publish_signups_changed(dinner: Uuid, promoted: Vec<DinnerSignup>) {
+    for signup in promoted {
+        EventBus::global().publish(
+            EventRecipient::Account(signup.account),
+            WsMessage::PromotedFromWaitlist { dinner },
+        );
+    }
+    EventBus::global().publish(
+        EventRecipient::All,
+        WsMessage::DinnerSignupsChanged { dinner },
+    );
+}
+
+/// Validates the capacity of a dinner
+fn check_capacity(capacity: Option<i32>) -> ApiResult<()> {
+    if capacity.is_some_and(|capacity| capacity < 1) {
+        return Err(ApiError::bad_request("Capacity has to be at least 1"));
+    }
+    Ok(())
+}
+
+/// Validates the user provided texts of a dinner
+fn parse_details(title: String, description: String) -> ApiResult<(MaxStr<255>, MaxStr<4096>)> {
+    if title.trim().is_empty() {
+        return Err(ApiError::bad_request("Title must not be empty"));
+    }
+    let title = MaxStr::new(title).map_err(|_| ApiError::bad_request("Title is too long"))?;
+    let description =
+        MaxStr::new(description).map_err(|_| ApiError::bad_request("Description is too long"))?;
+    Ok((title, description))
+}
+
+/// Checks whether an account may assign a cook to a dinner
+///
+/// Accounts may assign themselves while admins may assign anyone.
+/// The cook has to be an active account with the `Cook` role.
+async fn check_cook(tx: &mut Transaction, account: &Account, cook: Uuid) -> ApiResult<()> {
+    if cook != account.uuid && !account.has_role(&mut *tx, AccountRole::Admin).await? {
+        return Err(ApiError::new(
+            ApiStatusCode::MissingPrivileges,
+            "Assigning another cook requires the Admin role",
+        ));
+    }
+
+    let cook = Account::find_by_uuid(&mut *tx, cook)
+        .await?
+        .filter(|cook| !cook.is_deactivated())
+        .ok_or(ApiError::bad_request("Cook does not exist"))?;
+    if !cook.has_role(&mut *tx, AccountRole::Cook).await? {
+        return Err(ApiError::bad_request("Cook lacks the Cook role"));
+    }
+    Ok(())
+}
+
+/// Find a dinner which may be managed by an account
+///
+/// Dinners are managed by their cook and admins.
+/// The dinner is locked until the transaction ends, so its sign-ups can be counted safely.
+async fn find_managed_dinner(
+    tx: &mut Transaction,
+    account: &Account,
+    uuid: Uuid,
+) -> ApiResult<Dinner> {
+    Dinner::lock(&mut *tx, uuid).await?;
+    let dinner = Dinner::find_by_uuid(&mut *tx, uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Dinner does not exist"))?;
+    if dinner.cook != account.uuid && !account.has_role(&mut *tx, AccountRole::Admin).await? {
+        return Err(ApiError::new(
+            ApiStatusCode::MissingPrivileges,
+            "Only the cook may manage the dinner",
+        ));
+    }
+    Ok(dinner)
+}
+
+/// Converts dinners into their api representation
+async fn full_dinners(tx: &mut Transaction, dinners: Vec<Dinner>) -> ApiResult<Vec<FullDinner>> {
+    let dinner_uuids: Vec<_> = dinners.iter().map(|dinner| dinner.uuid).collect();
+    let mut signups: HashMap<_, Vec<_>> = HashMap::new();
+    for signup in DinnerSignup::find_all_by_dinners(&mut *tx, &dinner_uuids).await? {
+        signups.entry(signup.dinner).or_default().push(signup);
+    }
+
+    let account_uuids: Vec<_> = dinners
+        .iter()
+        .map(|dinner| dinner.cook)
+        .chain(signups.values().flatten().map(|signup| signup.account))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let accounts: HashMap<_, _> = Account::find_all_by_uuids(&mut *tx, &account_uuids)
+        .await?
+        .into_iter()
+        .map(|account| {
+            (
+                account.uuid,
+                SimpleAccount {
+                    uuid: account.uuid,
+                    display_name: account.display_name.to_string(),
+                },
+            )
+        })
+        .collect();
+
+    dinners
+        .into_iter()
+        .map(|dinner| {
+            let cook = accounts
+                .get(&dinner.cook)
+                .cloned()
+                .ok_or(ApiError::server_error("Cook of dinner does not exist"))?;
+            let (waitlist, attendees): (Vec<_>, Vec<_>) = signups
+                .remove(&dinner.uuid)
+                .unwrap_or_default()
+                .into_iter()
+                .partition(|signup| signup.waitlisted);
+            let to_attendees = |signups: Vec<DinnerSignup>| {
+                signups
+                    .into_iter()
+                    .map(|signup| {
+                        Ok(DinnerAttendee {
+                            account: accounts
+                                .get(&signup.account)
+                                .cloned()
+                                .ok_or(ApiError::server_error("Attendee does not exist"))?,
+                            portions: signup.portions,
+                            signed_up_at: signup.created_at,
+                            cancelled_at: signup.cancelled_at,
+                        })
+                    })
+                    .collect::<ApiResult<Vec<_>>>()
+            };
+            let attendees = to_attendees(attendees)?;
+            let waitlist = to_attendees(waitlist)?;
+            Ok(FullDinner {
+                uuid: dinner.uuid,
+                date: dinner.date,
+                title: dinner.title.to_string(),
+                description: dinner.description.to_string(),
+                cook,
+                state: dinner.state,
+                portions: attendees
+                    .iter()
+                    .filter(|attendee| attendee.cancelled_at.is_none())
+                    .map(|attendee| attendee.portions)
+                    .sum(),
+                signup_deadline: dinner.signups_close_at(),
+                capacity: dinner.capacity,
+                attendees,
+                waitlist,
+                created_at: dinner.created_at,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,278 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use time::Date;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::http::handler_frontend::accounts::schema::SimpleAccount;
+use crate::models::dinners::DinnerState;
+
+/// The full representation of a dinner
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FullDinner {
+    /// The dinner's primary key
+    pub uuid: Uuid,
+
+    /// The day the dinner takes place (e.g. `2025-01-31`)
+    #[schemars(with = "String")]
+    pub date: Date,
+
+    /// What is going to be cooked
+    pub title: String,
+
+    /// Further details like ingredients or allergens
+    pub description: String,
+
+    /// The account cooking the dinner
+    pub cook: SimpleAccount,
+
+    /// The state of the dinner
+    pub state: DinnerState,
+
+    /// The point in time sign-ups close
+    ///
+    /// Afterward, only the cook may add attendees or change sign-ups.
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub signup_deadline: OffsetDateTime,
+
+    /// Maximum number of portions (i.e. seats) for the dinner
+    ///
+    /// This is `None` if the dinner is not limited.
+    pub capacity: Option<i32>,
+
+    /// The accounts signed up for the dinner in the order they signed up
+    pub attendees: Vec<DinnerAttendee>,
+
+    /// The accounts waiting for a free seat in the order they will be promoted
+    pub waitlist: Vec<DinnerAttendee>,
+
+    /// Total number of portions to cook
+    ///
+    /// Late cancellations are not included.
+    pub portions: i32,
+
+    /// The point in time the dinner was created
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub created_at: OffsetDateTime,
+}
+
+/// The request to create a dinner
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateDinnerRequest {
+    /// The day the dinner takes place (e.g. `2025-01-31`)
+    #[schemars(with = "String")]
+    pub date: Date,
+
+    /// What is going to be cooked
+    pub title: String,
+
+    /// Further details like ingredients or allergens
+    #[serde(default)]
+    pub description: String,
+
+    /// The account cooking the dinner
+    ///
+    /// Defaults to the logged-in account.
+    /// Assigning another account requires the `Admin` role.
+    pub cook: Option<Uuid>,
+
+    /// The point in time sign-ups close
+    ///
+    /// Defaults to the configured time on the dinner's day.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub signup_deadline: Option<OffsetDateTime>,
+
+    /// Maximum number of portions (i.e. seats) for the dinner
+    ///
+    /// Sign-ups beyond it are put on the waitlist.
+    /// Defaults to no limit.
+    #[serde(default)]
+    pub capacity: Option<i32>,
+}
+
+/// The request to change a dinner
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateDinnerRequest {
+    /// The day the dinner takes place (e.g. `2025-01-31`)
+    #[schemars(with = "String")]
+    pub date: Date,
+
+    /// What is going to be cooked
+    pub title: String,
+
+    /// Further details like ingredients or allergens
+    pub description: String,
+
+    /// The account cooking the dinner
+    ///
+    /// Assigning another account requires the `Admin` role.
+    pub cook: Uuid,
+
+    /// The state of the dinner
+    pub state: DinnerState,
+
+    /// The point in time sign-ups close
+    ///
+    /// Defaults to the configured time on the dinner's day.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub signup_deadline: Option<OffsetDateTime>,
+
+    /// Maximum number of portions (i.e. seats) for the dinner
+    ///
+    /// Sign-ups beyond it are put on the waitlist.
+    /// Raising or removing it promotes waiting sign-ups.
+    #[serde(default)]
+    pub capacity: Option<i32>,
+}
+
+/// An account signed up for a dinner
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerAttendee {
+    /// The account which signed up
+    pub account: SimpleAccount,
+
+    /// Number of portions including the ones for the account's guests
+    pub portions: i32,
+
+    /// The point in time the account signed up
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub signed_up_at: OffsetDateTime,
+
+    /// The point in time the account cancelled after the sign-up deadline
+    ///
+    /// Late cancellations may still be charged.
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub cancelled_at: Option<OffsetDateTime>,
+}
+
+/// The request to sign up for a dinner or to change a sign-up
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerSignupRequest {
+    /// Number of portions including the ones for guests
+    ///
+    /// Has to be between 1 and 10.
+    pub portions: i32,
+}
+
+/// The response to signing up for a dinner
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerSignupResponse {
+    /// Whether the sign-up was put on the waitlist because the dinner is full
+    ///
+    /// The account is notified through the websocket once it is promoted.
+    pub waitlisted: bool,
+}
+
+/// Path identifying an account's sign-up for a dinner
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerSignupPath {
+    /// The dinner's primary key
+    pub uuid: Uuid,
+
+    /// The account's primary key
+    pub account: Uuid,
+}
+
+/// The request to remove an account's sign-up
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RemoveDinnerSignupRequest {
+    /// Keep the sign-up as late cancellation which may still be charged
+    #[serde(default)]
+    pub charge: bool,
+}
+
+/// The page of dinners to retrieve
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetAllDinnersRequest {
+    /// The maximum number of dinners to retrieve
+    ///
+    /// Defaults to and is capped at `100`.
+    pub limit: Option<u64>,
+
+    /// The number of dinners to skip
+    #[serde(default)]
+    pub offset: u64,
+}
+
+/// The days to retrieve the dinner calendar for
+///
+/// Either `year` and `week` or `from` and `to` have to be set.
+/// Without any of them, the current week is returned.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetDinnerCalendarRequest {
+    /// The ISO week-numbering year
+    pub year: Option<i32>,
+
+    /// The ISO week (1 to 53)
+    pub week: Option<u8>,
+
+    /// The first day to include (e.g. `2025-01-27`)
+    #[schemars(with = "Option<String>")]
+    pub from: Option<Date>,
+
+    /// The last day to include (e.g. `2025-01-31`)
+    #[schemars(with = "Option<String>")]
+    pub to: Option<Date>,
+}
+
+/// The dinners of consecutive days
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerCalendar {
+    /// The first day of the calendar
+    #[schemars(with = "String")]
+    pub from: Date,
+
+    /// The last day of the calendar
+    #[schemars(with = "String")]
+    pub to: Date,
+
+    /// The current day in the server's timezone
+    #[schemars(with = "String")]
+    pub today: Date,
+
+    /// The server's timezone (e.g. `Europe/Berlin`)
+    pub timezone: String,
+
+    /// Every day between `from` and `to`, including days without dinners
+    pub days: Vec<DinnerCalendarDay>,
+}
+
+/// A day in the dinner calendar
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerCalendarDay {
+    /// The day
+    #[schemars(with = "String")]
+    pub date: Date,
+
+    /// Number of sign-ups for the day's dinners
+    pub signups: usize,
+
+    /// Number of portions for the day's dinners
+    pub portions: i32,
+
+    /// The dinners taking place on the day
+    pub dinners: Vec<DinnerCalendarEntry>,
+}
+
+/// A dinner in the dinner calendar
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DinnerCalendarEntry {
+    /// The dinner including its cook and attendees
+    pub dinner: FullDinner,
+
+    /// The portions the logged-in account signed up for
+    ///
+    /// This is `None` if the account did not sign up or is waitlisted.
+    pub my_portions: Option<i32>,
+
+    /// The logged-in account's position on the waitlist starting at 1
+    pub my_waitlist_position: Option<usize>,
+}
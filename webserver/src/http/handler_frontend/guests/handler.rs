@@ -0,0 +1,223 @@
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::re_exports::axum::response::Redirect;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::core::stuff::schema::List;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::get;
+use galvyn::post;
+use galvyn::put;
+use galvyn::rorm::Database;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::db::transaction::Transaction;
+use galvyn::rorm::fields::types::MaxStr;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::http::handler_frontend::guests::schema::CreateGuestRequest;
+use crate::http::handler_frontend::guests::schema::GuestLoginLink;
+use crate::http::handler_frontend::guests::schema::GuestLoginPath;
+use crate::http::handler_frontend::guests::schema::SimpleGuest;
+use crate::http::handler_frontend::guests::schema::UpdateGuestRequest;
+use crate::http::handler_frontend::oidc::handler::SESSION_KEY_LOGOUT;
+use crate::models::account_sessions::ClientInfo;
+use crate::models::accounts::Account;
+use crate::models::guest_login_tokens::GuestLoginToken;
+use crate::models::login_attempts::GUEST_LOGIN_LINK;
+use crate::models::login_attempts::LoginAttempt;
+use crate::modules::oidc::OidcLogoutState;
+
+/// Retrieve the guests sponsored by the logged-in account
+#[get("/")]
+pub async fn get_guests(account: Account) -> ApiResult<ApiJson<List<SimpleGuest>>> {
+    let list = Account::find_guests_by_sponsor(Database::global(), account.uuid)
+        .await?
+        .into_iter()
+        .map(|guest| SimpleGuest {
+            uuid: guest.uuid,
+            display_name: guest.display_name.to_string(),
+            email: guest.email.map(|email| email.to_string()),
+            balance: guest.balance,
+            bill_sponsor: guest.bill_sponsor,
+            deactivated_at: guest.deactivated_at,
+        })
+        .collect();
+    Ok(ApiJson(List { list }))
+}
+
+/// Create a guest account sponsored by the logged-in account
+///
+/// Guests can't sponsor other guests.
+#[post("/")]
+pub async fn create_guest(
+    account: Account,
+    ApiJson(request): ApiJson<CreateGuestRequest>,
+) -> ApiResult<ApiJson<SingleUuid>> {
+    if account.is_guest() {
+        return Err(ApiError::new(
+            ApiStatusCode::MissingPrivileges,
+            "Guests can't sponsor other guests",
+        ));
+    }
+    if request.display_name.trim().is_empty() {
+        return Err(ApiError::bad_request("Name must not be empty"));
+    }
+    let display_name =
+        MaxStr::new(request.display_name).map_err(|_| ApiError::bad_request("Name is too long"))?;
+    let email = request
+        .email
+        .map(MaxStr::new)
+        .transpose()
+        .map_err(|_| ApiError::bad_request("Email is too long"))?;
+
+    let guest = Account::create_guest(
+        Database::global(),
+        account.uuid,
+        display_name,
+        email,
+        request.bill_sponsor,
+    )
+    .await?;
+
+    Ok(ApiJson(SingleUuid { uuid: guest.uuid }))
+}
+
+/// Change a guest sponsored by the logged-in account
+#[put("/{uuid}")]
+pub async fn update_guest(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(request): ApiJson<UpdateGuestRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut guest = find_guest(&mut tx, &account, uuid).await?;
+    guest
+        .set_bill_sponsor(&mut tx, request.bill_sponsor)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Create a link which logs a guest sponsored by the logged-in account in once
+///
+/// The link expires after a week.
+#[post("/{uuid}/login-link")]
+pub async fn create_guest_login_link(
+    account: Account,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<ApiJson<GuestLoginLink>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let guest = find_guest(&mut tx, &account, uuid).await?;
+    if guest.is_deactivated() {
+        return Err(ApiError::bad_request("Guest is deactivated"));
+    }
+    let (token, secret) = GuestLoginToken::create(&mut tx, guest.uuid).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(GuestLoginLink {
+        url: format!("/guest-login/{secret}"),
+        expires_at: token.expires_at,
+    }))
+}
+
+/// Redirects a login link to the frontend's page for it
+///
+/// Only the frontend redeems the token, so that merely opening a link
+/// (e.g. by a link preview) doesn't consume it.
+#[get("/{token}")]
+pub async fn open_guest_login_link(
+    Path(GuestLoginPath { token }): Path<GuestLoginPath>,
+) -> ApiResult<Redirect> {
+    if !token
+        .chars()
+        .all(|char| char.is_ascii_alphanumeric() || char == '_')
+    {
+        return Err(ApiError::bad_request("Login link is invalid"));
+    }
+    Ok(Redirect::temporary(&format!("/guest-login/{token}")))
+}
+
+/// Logs a guest in using a link created by its sponsor
+///
+/// The link can only be used once.
+/// An account logged in the session before is logged out.
+#[post("/{token}")]
+pub async fn guest_login(
+    session: Session,
+    client: ClientInfo,
+    Path(GuestLoginPath { token }): Path<GuestLoginPath>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let Some(token) = GuestLoginToken::redeem(&mut tx, &token).await? else {
+        return login_failed(tx, None, "Login link is invalid or expired", client).await;
+    };
+    let Some(mut account) = Account::find_by_uuid(&mut tx, token.account)
+        .await?
+        .filter(|account| !account.is_deactivated())
+    else {
+        return login_failed(tx, Some(token.account), "Account is deactivated", client).await;
+    };
+
+    // End the login of the session's previous account, including its oidc logout state
+    session
+        .remove::<OidcLogoutState>(SESSION_KEY_LOGOUT)
+        .await?;
+    Account::set_logged_out(&session).await?;
+
+    account
+        .set_logged_in(&mut tx, &session, None, client.clone())
+        .await?;
+    LoginAttempt::create(
+        &mut tx,
+        GUEST_LOGIN_LINK,
+        None,
+        Some(account.uuid),
+        None,
+        client,
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(account.uuid = %account.uuid, "Guest logged in");
+    Ok(())
+}
+
+/// Records a failed guest login and responds with the reason
+///
+/// The redeemed token stays consumed.
+async fn login_failed(
+    mut tx: Transaction,
+    account: Option<Uuid>,
+    reason: &'static str,
+    client: ClientInfo,
+) -> ApiResult<()> {
+    LoginAttempt::create(
+        &mut tx,
+        GUEST_LOGIN_LINK,
+        None,
+        account,
+        Some(reason),
+        client,
+    )
+    .await?;
+    tx.commit().await?;
+    Err(ApiError::unauthorized(reason))
+}
+
+/// Find a guest sponsored by an account
+async fn find_guest(exe: impl Executor<'_>, sponsor: &Account, uuid: Uuid) -> ApiResult<Account> {
+    Account::find_by_uuid(exe, uuid)
+        .await?
+        .filter(|guest| guest.sponsor == Some(sponsor.uuid))
+        .ok_or(ApiError::bad_request("Guest does not exist"))
+}
@@ -0,0 +1,75 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// A guest account sponsored by the logged-in account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimpleGuest {
+    /// The guest's primary key
+    pub uuid: Uuid,
+
+    /// The name that is used for displaying purposes
+    pub display_name: String,
+
+    /// The guest's email address
+    pub email: Option<String>,
+
+    /// Current balance of the guest
+    ///
+    /// This stays `0` while charges are billed to the sponsor.
+    pub balance: i64,
+
+    /// Whether charges of the guest are billed to the sponsor
+    pub bill_sponsor: bool,
+
+    /// The point in time the guest was deactivated
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub deactivated_at: Option<OffsetDateTime>,
+}
+
+/// The request to create a guest account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateGuestRequest {
+    /// The name that is used for displaying purposes
+    pub display_name: String,
+
+    /// The guest's email address
+    #[serde(default)]
+    pub email: Option<String>,
+
+    /// Bill charges of the guest to the sponsor instead of the guest's own balance
+    #[serde(default)]
+    pub bill_sponsor: bool,
+}
+
+/// The request to change a guest account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateGuestRequest {
+    /// Bill charges of the guest to the sponsor instead of the guest's own balance
+    pub bill_sponsor: bool,
+}
+
+/// A link logging a guest in once
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuestLoginLink {
+    /// The path of the link which has to be handed to the guest
+    ///
+    /// It can't be retrieved again.
+    pub url: String,
+
+    /// The point in time after which the link is no longer valid
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub expires_at: OffsetDateTime,
+}
+
+/// Path containing a guest login token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuestLoginPath {
+    /// The secret token of the link
+    pub token: String,
+}
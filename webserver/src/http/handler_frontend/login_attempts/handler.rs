@@ -0,0 +1,47 @@
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Query;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::List;
+use galvyn::get;
+use galvyn::rorm::Database;
+
+use crate::http::handler_frontend::login_attempts::schema::FullLoginAttempt;
+use crate::http::handler_frontend::login_attempts::schema::GetLoginAttemptsRequest;
+use crate::models::login_attempts::LoginAttempt;
+
+/// Maximum number of login attempts retrieved by a single request
+const MAX_LIMIT: u64 = 100;
+
+/// Retrieve a page of the recorded login attempts, newest first
+///
+/// Attempts are kept for `LOGIN_LOG_RETENTION_DAYS`.
+///
+/// Requires the `Admin` role.
+#[get("/")]
+pub async fn get_login_attempts(
+    Query(request): Query<GetLoginAttemptsRequest>,
+) -> ApiResult<ApiJson<List<FullLoginAttempt>>> {
+    let list = LoginAttempt::find_page(
+        Database::global(),
+        request.account,
+        request.failed_only,
+        request.limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT),
+        request.offset,
+    )
+    .await?
+    .into_iter()
+    .map(|attempt| FullLoginAttempt {
+        uuid: attempt.uuid,
+        provider: attempt.provider.to_string(),
+        subject: attempt.subject.map(|subject| subject.to_string()),
+        account: attempt.account,
+        success: attempt.success,
+        failure_reason: attempt.failure_reason.map(|reason| reason.to_string()),
+        user_agent: attempt.user_agent.map(|user_agent| user_agent.to_string()),
+        ip: attempt.ip.map(|ip| ip.to_string()),
+        created_at: attempt.created_at,
+    })
+    .collect();
+    Ok(ApiJson(List { list }))
+}
@@ -0,0 +1,59 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Filters for the recorded login attempts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GetLoginAttemptsRequest {
+    /// Only retrieve the attempts which logged this account in
+    pub account: Option<Uuid>,
+
+    /// Only retrieve failed attempts
+    #[serde(default)]
+    pub failed_only: bool,
+
+    /// The maximum number of attempts to retrieve
+    ///
+    /// Defaults to and is capped at `100`.
+    pub limit: Option<u64>,
+
+    /// The number of attempts to skip
+    #[serde(default)]
+    pub offset: u64,
+}
+
+/// A recorded attempt to log in
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FullLoginAttempt {
+    /// The attempt's primary key
+    pub uuid: Uuid,
+
+    /// The oidc provider used to log in or `guest-login-link`
+    pub provider: String,
+
+    /// The subject the oidc provider authenticated
+    pub subject: Option<String>,
+
+    /// The account which has been logged in
+    pub account: Option<Uuid>,
+
+    /// Whether the login succeeded
+    pub success: bool,
+
+    /// Why the login failed
+    pub failure_reason: Option<String>,
+
+    /// The `User-Agent` of the browser attempting to log in
+    pub user_agent: Option<String>,
+
+    /// The ip address the attempt originated from
+    pub ip: Option<String>,
+
+    /// The point in time of the attempt
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub created_at: OffsetDateTime,
+}
@@ -6,19 +6,140 @@ use galvyn::core::GalvynRouter;
 use galvyn::openapi::OpenapiRouterExt;
 
 use crate::http::middlewares::auth_required::AuthRequiredLayer;
+use crate::http::middlewares::csrf_protection::CsrfProtectionLayer;
+use crate::http::middlewares::impersonation_log::ImpersonationLogLayer;
+use crate::http::middlewares::oidc_provider_available::OidcProviderAvailableLayer;
+use crate::http::middlewares::role_required::RoleRequiredLayer;
+use crate::models::accounts::AccountRole;
 
+pub mod accounts;
+pub mod api_tokens;
+pub mod dinners;
+pub mod guests;
+pub mod login_attempts;
 pub mod oidc;
+pub mod sessions;
+pub mod ws;
 
 /// Initialize the routes of the frontend
 pub fn initialize_routes() -> GalvynRouter {
-    let without_auth = GalvynRouter::new().nest(
-        "/oidc",
-        GalvynRouter::new().openapi_tag("OpenId Connect"),
-        //.handler(oidc::handler::begin_oidc_login)
-        //.handler(oidc::handler::finish_oidc_login)
-    );
+    let without_auth = GalvynRouter::new()
+        .nest(
+            "/oidc",
+            GalvynRouter::new()
+                .openapi_tag("OpenId Connect")
+                .handler(oidc::handler::get_oidc_providers)
+                .handler(oidc::handler::logout)
+                .merge(
+                    GalvynRouter::new()
+                        .openapi_tag("OpenId Connect")
+                        .handler(oidc::handler::begin_oidc_login)
+                        .handler(oidc::handler::finish_oidc_login)
+                        .handler(oidc::handler::backchannel_logout)
+                        .wrap(OidcProviderAvailableLayer),
+                ),
+        )
+        .nest(
+            "/guest-login",
+            GalvynRouter::new()
+                .openapi_tag("Guests")
+                .handler(guests::handler::open_guest_login_link)
+                .handler(guests::handler::guest_login),
+        );
 
-    let with_auth = GalvynRouter::new();
+    let with_auth = GalvynRouter::new()
+        .nest(
+            "/accounts",
+            GalvynRouter::new()
+                .openapi_tag("Accounts")
+                .handler(accounts::handler::get_me)
+                .handler(accounts::handler::stop_impersonation)
+                .merge(
+                    GalvynRouter::new()
+                        .openapi_tag("Accounts")
+                        .handler(accounts::handler::get_all_accounts)
+                        .handler(accounts::handler::set_account_roles)
+                        .handler(accounts::handler::get_account_sessions)
+                        .handler(accounts::handler::revoke_account_session)
+                        .handler(accounts::handler::revoke_account_sessions)
+                        .handler(accounts::handler::deactivate_account)
+                        .handler(accounts::handler::reactivate_account)
+                        .handler(accounts::handler::impersonate_account)
+                        .handler(accounts::handler::get_impersonation_log)
+                        .wrap(RoleRequiredLayer(AccountRole::Admin)),
+                )
+                .merge(
+                    GalvynRouter::new()
+                        .openapi_tag("Accounts")
+                        .handler(accounts::handler::adjust_account_balance)
+                        .wrap(RoleRequiredLayer(AccountRole::Treasurer)),
+                ),
+        )
+        .nest(
+            "/api-tokens",
+            GalvynRouter::new()
+                .openapi_tag("Api Tokens")
+                .handler(api_tokens::handler::get_api_tokens)
+                .handler(api_tokens::handler::create_api_token)
+                .handler(api_tokens::handler::delete_api_token),
+        )
+        .nest(
+            "/dinners",
+            GalvynRouter::new()
+                .openapi_tag("Dinners")
+                .handler(dinners::handler::get_all_dinners)
+                .handler(dinners::handler::get_dinner_calendar)
+                .handler(dinners::handler::get_dinner)
+                .handler(dinners::handler::update_dinner)
+                .handler(dinners::handler::delete_dinner)
+                .handler(dinners::handler::join_dinner)
+                .handler(dinners::handler::change_dinner_signup)
+                .handler(dinners::handler::leave_dinner)
+                .handler(dinners::handler::set_attendee_signup)
+                .handler(dinners::handler::remove_attendee_signup)
+                .merge(
+                    GalvynRouter::new()
+                        .openapi_tag("Dinners")
+                        .handler(dinners::handler::create_dinner)
+                        .wrap(RoleRequiredLayer(AccountRole::Cook)),
+                ),
+        )
+        .nest(
+            "/guests",
+            GalvynRouter::new()
+                .openapi_tag("Guests")
+                .handler(guests::handler::get_guests)
+                .handler(guests::handler::create_guest)
+                .handler(guests::handler::update_guest)
+                .handler(guests::handler::create_guest_login_link),
+        )
+        .nest(
+            "/login-attempts",
+            GalvynRouter::new()
+                .openapi_tag("Login Attempts")
+                .handler(login_attempts::handler::get_login_attempts)
+                .wrap(RoleRequiredLayer(AccountRole::Admin)),
+        )
+        .nest(
+            "/sessions",
+            GalvynRouter::new()
+                .openapi_tag("Sessions")
+                .handler(sessions::handler::get_sessions)
+                .handler(sessions::handler::revoke_session)
+                .handler(sessions::handler::revoke_other_sessions),
+        )
+        .nest(
+            "/ws",
+            GalvynRouter::new()
+                .openapi_tag("Websocket")
+                .handler(ws::handler::websocket),
+        );
 
-    without_auth.merge(with_auth.wrap(AuthRequiredLayer))
+    without_auth
+        .merge(
+            with_auth
+                .wrap(ImpersonationLogLayer)
+                .wrap(AuthRequiredLayer),
+        )
+        .wrap(CsrfProtectionLayer)
 }
@@ -1,47 +1,104 @@
+use std::ops::Deref;
+
 use galvyn::core::Module;
+use galvyn::core::re_exports::axum::Form;
+use galvyn::core::re_exports::axum::extract::Path;
 use galvyn::core::re_exports::axum::extract::Query;
 use galvyn::core::re_exports::axum::response::Redirect;
-use galvyn::core::re_exports::uuid::Uuid;
 use galvyn::core::session::Session;
 use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::List;
 use galvyn::get;
 use galvyn::post;
-use galvyn::rorm;
 use galvyn::rorm::Database;
-use galvyn::rorm::and;
+use galvyn::rorm::db::transaction::Transaction;
 use galvyn::rorm::fields::types::MaxStr;
-use galvyn::rorm::model::Identifiable;
-use galvyn::rorm::prelude::ForeignModelByField;
+use tracing::info;
 use tracing::trace;
 
+use crate::http::handler_frontend::oidc::schema::BackchannelLogoutRequest;
+use crate::http::handler_frontend::oidc::schema::BeginOidcLoginRequest;
 use crate::http::handler_frontend::oidc::schema::FinishOidcLoginRequest;
+use crate::http::handler_frontend::oidc::schema::LogoutResponse;
+use crate::http::handler_frontend::oidc::schema::OidcProviderPath;
+use crate::http::handler_frontend::oidc::schema::SimpleOidcProvider;
+use crate::models::account_sessions::AccountSession;
+use crate::models::account_sessions::ClientInfo;
 use crate::models::accounts::Account;
+use crate::models::login_attempts::LoginAttempt;
+use crate::modules::event_bus::EventBus;
+use crate::modules::ldap::Ldap;
+use crate::modules::oidc;
+use crate::modules::oidc::OidcIdTokenClaims;
+use crate::modules::oidc::OidcLoginError;
+use crate::modules::oidc::OidcLogoutState;
+use crate::modules::oidc::OidcProvider;
 use crate::modules::oidc::OidcRequestState;
 use crate::modules::oidc::OpenIdConnect;
 
-#[get("/begin-login")]
-pub async fn begin_oidc_login(session: Session) -> ApiResult<Redirect> {
-    let (auth_url, session_state) = OpenIdConnect::global().begin_login()?;
+/// Retrieve the oidc providers users can log in with
+#[get("/providers")]
+pub async fn get_oidc_providers() -> ApiResult<ApiJson<List<SimpleOidcProvider>>> {
+    Ok(ApiJson(List {
+        list: OpenIdConnect::global()
+            .providers()
+            .map(|provider| SimpleOidcProvider {
+                name: provider.name.clone(),
+                available: provider.is_available(),
+            })
+            .collect(),
+    }))
+}
+
+/// Redirects the user to the oidc provider to start the login
+///
+/// After the login, the user is redirected to `return_to`.
+#[get("/{provider}/begin-login")]
+pub async fn begin_oidc_login(
+    session: Session,
+    Path(OidcProviderPath { provider }): Path<OidcProviderPath>,
+    Query(request): Query<BeginOidcLoginRequest>,
+) -> ApiResult<Redirect> {
+    if request
+        .return_to
+        .as_deref()
+        .is_some_and(|return_to| !is_relative_path(return_to))
+    {
+        return Err(ApiError::bad_request("return_to has to be a relative path"));
+    }
+
+    let (auth_url, session_state) = OpenIdConnect::global()
+        .provider(&provider)?
+        .begin_login(request.return_to)?;
 
     session.insert(SESSION_KEY, session_state).await?;
 
     Ok(Redirect::temporary(auth_url.as_str()))
 }
 
-#[get("/finish-login")]
+/// The oidc provider redirects the user back to this endpoint after a successful authentication
+///
+/// Accounts are created on the first login of a subject.
+#[get("/{provider}/finish-login")]
 pub async fn finish_oidc_login(
     session: Session,
+    client: ClientInfo,
+    Path(OidcProviderPath { provider }): Path<OidcProviderPath>,
     Query(request): Query<FinishOidcLoginRequest>,
 ) -> ApiResult<Redirect> {
+    let provider = OpenIdConnect::global().provider(&provider)?;
+
     let session_state = session
         .remove(SESSION_KEY)
         .await?
         .ok_or(ApiError::bad_request(
             "There is no unfinished login challenge",
         ))?;
+    let return_to = session_state.return_to.clone();
 
-    let claims = OpenIdConnect::global()
+    let result = provider
         .finish_login(
             session_state,
             OidcRequestState {
@@ -49,44 +106,222 @@ pub async fn finish_oidc_login(
                 state: request.state.0,
             },
         )
-        .await?;
+        .await;
+    let (claims, id_token) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            LoginAttempt::create(
+                Database::global(),
+                &provider.name,
+                None,
+                None,
+                Some(error.reason),
+                client,
+            )
+            .await?;
+            return Err(error.into());
+        }
+    };
 
     trace!(claims = serde_json::to_string(&claims).unwrap_or_else(|error| error.to_string()));
 
     let mut tx = Database::global().start_transaction().await?;
 
-    // TODO: check issuer?
+    let mut account = match find_or_create_account(&mut tx, provider, &claims).await {
+        Ok(account) => account,
+        Err(error) => {
+            drop(tx);
+            LoginAttempt::create(
+                Database::global(),
+                &provider.name,
+                Some(claims.subject().as_str()),
+                None,
+                Some(error.reason),
+                client,
+            )
+            .await?;
+            return Err(error.into());
+        }
+    };
+
+    let sid = oidc::session_id(&claims)
+        .map(MaxStr::new)
+        .transpose()
+        .map_err(ApiError::map_server_error("Session id is too long"))?;
+    account
+        .set_logged_in(&mut tx, &session, sid, client.clone())
+        .await?;
+    session
+        .insert(
+            SESSION_KEY_LOGOUT,
+            OidcLogoutState {
+                provider: provider.name.clone(),
+                id_token,
+            },
+        )
+        .await?;
+    LoginAttempt::create(
+        &mut tx,
+        &provider.name,
+        Some(claims.subject().as_str()),
+        Some(account.uuid),
+        None,
+        client,
+    )
+    .await?;
+
+    tx.commit().await?;
 
+    Ok(Redirect::temporary(return_to.as_deref().unwrap_or("/")))
+}
+
+/// Finds the account of the subject authenticated by an oidc provider
+///
+/// The account is created on the subject's first login.
+/// Otherwise, its details and roles are updated from the claims.
+async fn find_or_create_account(
+    tx: &mut Transaction,
+    provider: &OidcProvider,
+    claims: &OidcIdTokenClaims,
+) -> Result<Account, OidcLoginError> {
+    let issuer = MaxStr::new(claims.issuer().to_string())
+        .map_err(OidcLoginError::map_server_error("Issuer is too long"))?;
     let subject = MaxStr::new(claims.subject().to_string())
-        .map_err(ApiError::map_server_error("Subject is too long"))?;
+        .map_err(OidcLoginError::map_server_error("Subject is too long"))?;
 
-    let display_name = claims
-        .name()
-        .and_then(|localized| localized.get(None))
-        .ok_or(ApiError::server_error(
-            "Oidc provider did not provide the name claim",
-        ))?;
-    let display_name = MaxStr::new(display_name.to_string())
-        .map_err(ApiError::map_server_error("Name is too long"))?;
+    let display_name = provider
+        .display_name(claims)
+        .map(MaxStr::new)
+        .transpose()
+        .map_err(OidcLoginError::map_server_error("Name is too long"))?;
+    let email = provider
+        .email(claims)
+        .map(MaxStr::new)
+        .transpose()
+        .map_err(OidcLoginError::map_server_error("Email is too long"))?;
 
-    if let Some(mut account) = Account::find_by_subject(&mut tx, &subject).await? {
-        // Account exists
-        account.set_display_name(&mut tx, display_name).await?;
-        account.set_logged_in(&session).await?;
+    // Guest providers are not backed by the directory
+    let (display_name, ldap_dn) = if provider.ldap {
+        let username = claims
+            .preferred_username()
+            .map(|username| username.as_str())
+            .unwrap_or(claims.subject().as_str());
+        let ldap_user = Ldap::global()
+            .find_user(username)
+            .await
+            .map_err(OidcLoginError::map_server_error(
+                "Failed to query the directory",
+            ))?
+            .ok_or(OidcLoginError::unauthorized(
+                "User is not part of the directory",
+            ))?;
+        (ldap_user.display_name.or(display_name), Some(ldap_user.dn))
     } else {
-        // TODO: sync LDAP & create account
-        // account.set_logged_in(&session).await?;
+        (display_name, None)
     };
+    let display_name = display_name.ok_or(OidcLoginError::server_error(
+        "Oidc provider did not provide any of the display name claims",
+    ))?;
 
-    tx.commit().await?;
+    let mut account = match Account::find_by_subject(&mut *tx, &issuer, &subject, provider.ldap)
+        .await?
+    {
+        Some(account) if account.is_deactivated() => {
+            return Err(OidcLoginError::unauthorized("Account is deactivated"));
+        }
+        Some(mut account) => {
+            account.set_display_name(&mut *tx, display_name).await?;
+            if account.email.as_deref() != email.as_deref() {
+                account.set_email(&mut *tx, email).await?;
+            }
+            if let Some(ldap_dn) = ldap_dn
+                && (account.ldap_dn.as_deref() != Some(ldap_dn.deref()) || account.ldap_dn_missing)
+            {
+                account.set_ldap_dn(&mut *tx, ldap_dn).await?;
+            }
+            account
+        }
+        // First login of this subject
+        None => Account::create(&mut *tx, issuer, subject, display_name, email, ldap_dn).await?,
+    };
+
+    let current_roles = account.get_roles(&mut *tx).await?;
+    if let Some(roles) = provider.map_roles(claims, current_roles) {
+        account.set_roles(&mut *tx, &roles).await?;
+    }
+
+    Ok(account)
+}
 
-    Ok(Redirect::temporary("/"))
+/// Checks a path to stay on this host when used as redirect target
+///
+/// Browsers treat `//host` and `/\host` as urls to another host.
+fn is_relative_path(path: &str) -> bool {
+    path.starts_with('/')
+        && !path.starts_with("//")
+        && !path.contains('\\')
+        && !path.chars().any(char::is_control)
 }
 
 const SESSION_KEY: &str = "begin_oidc_login";
+/// Session key of the [`OidcLogoutState`] of the logged-in account
+pub const SESSION_KEY_LOGOUT: &str = "oidc_logout";
 
+/// Logs the current account out
+///
+/// If the oidc provider supports RP-initiated logout,
+/// the frontend should redirect the user to the returned url to end the session there as well.
+/// Otherwise, the user is only logged out locally.
 #[post("/logout")]
-pub async fn logout(session: Session) -> ApiResult<()> {
+pub async fn logout(session: Session) -> ApiResult<ApiJson<LogoutResponse>> {
+    let logout_state: Option<OidcLogoutState> = session.remove(SESSION_KEY_LOGOUT).await?;
     Account::set_logged_out(&session).await?;
+
+    let redirect_url = logout_state.and_then(|state| {
+        OpenIdConnect::global()
+            .provider(&state.provider)
+            .ok()?
+            .logout_url(&state.id_token)
+    });
+
+    Ok(ApiJson(LogoutResponse {
+        redirect_url: redirect_url.map(String::from),
+    }))
+}
+
+/// The oidc provider notifies this endpoint when a user logged out at the provider
+///
+/// This implements [OpenID Connect Back-Channel Logout](https://openid.net/specs/openid-connect-backchannel-1_0.html).
+/// If the logout token contains a session id (`sid`), only the login with this session id is ended.
+/// Otherwise, all logins of the subject are ended.
+#[post("/{provider}/backchannel-logout")]
+pub async fn backchannel_logout(
+    Path(OidcProviderPath { provider }): Path<OidcProviderPath>,
+    Form(request): Form<BackchannelLogoutRequest>,
+) -> ApiResult<()> {
+    let provider = OpenIdConnect::global().provider(&provider)?;
+    let claims = provider.verify_logout_token(&request.logout_token).await?;
+
+    let (Ok(issuer), Ok(subject)) = (MaxStr::new(claims.issuer), MaxStr::new(claims.subject))
+    else {
+        return Err(ApiError::bad_request("Unknown subject"));
+    };
+
+    let mut logged_out = Vec::new();
+    let mut tx = Database::global().start_transaction().await?;
+
+    // Unknown subjects don't have any sessions which could be ended
+    if let Some(account) = Account::find_by_subject(&mut tx, &issuer, &subject, false).await? {
+        let ended =
+            AccountSession::delete_by_oidc_sid(&mut tx, account.uuid, claims.sid.as_deref())
+                .await?;
+        info!(account.uuid = %account.uuid, ended = ended.len(), "Back-channel logout");
+        logged_out.extend(ended);
+    }
+
+    tx.commit().await?;
+
+    EventBus::global().publish_logged_out(logged_out);
+
     Ok(())
 }
@@ -6,8 +6,46 @@ use openidconnect::CsrfToken;
 use serde::Deserialize;
 use serde::Serialize;
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BeginOidcLoginRequest {
+    /// Path to redirect the user to after the login (e.g. `/dinners/…`)
+    ///
+    /// It has to be a path on this host. Defaults to `/`.
+    pub return_to: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FinishOidcLoginRequest {
     pub code: SchemaString<AuthorizationCode>,
     pub state: SchemaString<CsrfToken>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OidcProviderPath {
+    pub provider: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimpleOidcProvider {
+    /// Name identifying the provider in urls
+    pub name: String,
+
+    /// Whether the provider is reachable
+    ///
+    /// Logins with unavailable providers fail with `503 Service Unavailable`.
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LogoutResponse {
+    /// The url to redirect the user to, to log out at the oidc provider as well
+    ///
+    /// This is `None` if the provider does not support it.
+    pub redirect_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BackchannelLogoutRequest {
+    /// A jwt issued by the oidc provider identifying the session to end
+    pub logout_token: String,
+}
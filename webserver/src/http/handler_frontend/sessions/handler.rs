@@ -0,0 +1,76 @@
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::core::stuff::schema::List;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::rorm::Database;
+
+use crate::http::handler_frontend::sessions::schema::SimpleAccountSession;
+use crate::models::account_sessions::AccountSession;
+use crate::models::accounts::Account;
+use crate::modules::event_bus::EventBus;
+
+/// Retrieve the active sessions of the logged-in account
+#[get("/")]
+pub async fn get_sessions(
+    account: Account,
+    session: Session,
+) -> ApiResult<ApiJson<List<SimpleAccountSession>>> {
+    let current = Account::get_account_session(&session).await?;
+    let list = AccountSession::find_all_by_account(Database::global(), account.uuid)
+        .await?
+        .into_iter()
+        .map(|account_session| SimpleAccountSession::new(account_session, current))
+        .collect();
+    Ok(ApiJson(List { list }))
+}
+
+/// Revoke a session of the logged-in account
+///
+/// The revoked session is logged out with its next request.
+/// Admins impersonating an account can't revoke its sessions.
+#[delete("/{uuid}")]
+pub async fn revoke_session(
+    account: Account,
+    session: Session,
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    reject_impersonation(&session).await?;
+
+    if !AccountSession::delete(Database::global(), account.uuid, uuid).await? {
+        return Err(ApiError::bad_request("Session does not exist"));
+    }
+    EventBus::global().publish_logged_out([uuid]);
+    Ok(())
+}
+
+/// Revoke all sessions of the logged-in account except the one sending this request
+///
+/// Admins impersonating an account can't revoke its sessions.
+#[delete("/")]
+pub async fn revoke_other_sessions(account: Account, session: Session) -> ApiResult<()> {
+    reject_impersonation(&session).await?;
+
+    let current = Account::get_account_session(&session).await?;
+    let revoked =
+        AccountSession::delete_all_by_account(Database::global(), account.uuid, current).await?;
+    EventBus::global().publish_logged_out(revoked);
+    Ok(())
+}
+
+/// Rejects revoking the sessions of an impersonated account
+async fn reject_impersonation(session: &Session) -> ApiResult<()> {
+    if Account::get_impersonation(session).await?.is_some() {
+        return Err(ApiError::new(
+            ApiStatusCode::MissingPrivileges,
+            "Sessions can't be revoked while impersonating an account",
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::account_sessions::AccountSession;
+
+/// A login of an account through the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimpleAccountSession {
+    /// The session's primary key
+    pub uuid: Uuid,
+
+    /// The point in time the login happened
+    #[serde(with = "time::serde::rfc3339")]
+    #[schemars(with = "String")]
+    pub created_at: OffsetDateTime,
+
+    /// The point in time the session was last used
+    ///
+    /// This is only updated every few minutes.
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schemars(with = "Option<String>")]
+    pub last_seen_at: Option<OffsetDateTime>,
+
+    /// The `User-Agent` of the browser which logged in
+    pub user_agent: Option<String>,
+
+    /// The ip address the login originated from
+    pub ip: Option<String>,
+
+    /// Whether this is the session sending the request
+    pub current: bool,
+}
+
+impl SimpleAccountSession {
+    /// Converts a session into its api representation
+    ///
+    /// `current` is the uuid of the session sending the request.
+    pub fn new(session: AccountSession, current: Option<Uuid>) -> Self {
+        Self {
+            uuid: session.uuid,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            user_agent: session.user_agent.map(|user_agent| user_agent.to_string()),
+            ip: session.ip.map(|ip| ip.to_string()),
+            current: current == Some(session.uuid),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use galvyn::core::Module;
+use galvyn::core::handler::request_part::RequestPart;
+use galvyn::core::handler::request_part::ShouldBeRequestPart;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::extract::ws::Message;
+use galvyn::core::re_exports::axum::extract::ws::WebSocket;
+use galvyn::core::re_exports::axum::extract::ws::WebSocketUpgrade;
+use galvyn::core::re_exports::axum::http::request::Parts;
+use galvyn::core::re_exports::axum::response::Response;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::get;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::accounts::Account;
+use crate::modules::event_bus::Event;
+use crate::modules::event_bus::EventBus;
+use crate::modules::event_bus::WsMessage;
+
+/// Opens a websocket receiving events concerning the logged-in account
+///
+/// The server sends [`WsMessage`]s as json encoded text messages.
+/// Messages sent by the client are ignored.
+#[get("/")]
+pub async fn websocket(
+    account: Account,
+    session: Session,
+    WsUpgrade(upgrade): WsUpgrade,
+) -> ApiResult<Response> {
+    let account_session = Account::get_account_session(&session).await?;
+    let events = EventBus::global().subscribe();
+
+    Ok(upgrade
+        .on_upgrade(move |socket| handle_websocket(socket, account.uuid, account_session, events)))
+}
+
+/// Forwards the events addressed to an account's session to its websocket
+async fn handle_websocket(
+    mut socket: WebSocket,
+    account: Uuid,
+    account_session: Option<Uuid>,
+    mut events: broadcast::Receiver<Event>,
+) {
+    loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if !event.recipient.matches(account, account_session) {
+                        continue;
+                    }
+                    let logged_out = matches!(event.message, WsMessage::LoggedOut);
+                    let Ok(text) = serde_json::to_string(&event.message) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text.into())).await.is_err() || logged_out {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(account.uuid = %account, skipped, "Websocket missed events");
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    debug!(account.uuid = %account, "Websocket closed");
+}
+
+/// Extractor for [`WebSocketUpgrade`] which galvyn accepts as handler argument
+pub struct WsUpgrade(pub WebSocketUpgrade);
+
+impl<S: Send + Sync> FromRequestParts<S> for WsUpgrade {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        WebSocketUpgrade::from_request_parts(parts, state)
+            .await
+            .map(WsUpgrade)
+            .map_err(|_| ApiError::bad_request("Expected a websocket upgrade"))
+    }
+}
+
+impl ShouldBeRequestPart for WsUpgrade {}
+impl RequestPart for WsUpgrade {}
@@ -0,0 +1,61 @@
+//! Middleware which requires the request to carry a valid API token.
+
+use std::ops::ControlFlow;
+
+use galvyn::core::Module;
+use galvyn::core::middleware::SimpleGalvynMiddleware;
+use galvyn::core::re_exports::axum::extract::Request;
+use galvyn::core::re_exports::axum::http::header::AUTHORIZATION;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::rorm::Database;
+
+use crate::models::api_tokens::ApiToken;
+
+/// Middleware which requires the request to carry a valid API token.
+///
+/// The token is expected in the `Authorization: Bearer <token>` header.
+/// On success the [`ApiToken`] is stored in the request's extensions
+/// where the [`Account`](crate::models::accounts::Account) extractor picks it up.
+#[derive(Copy, Clone, Debug)]
+pub struct ApiTokenRequiredLayer;
+
+impl SimpleGalvynMiddleware for ApiTokenRequiredLayer {
+    async fn pre_handler(&mut self, mut req: Request) -> ControlFlow<Response, Request> {
+        let Some(token) = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            return ControlFlow::Break(
+                ApiError::unauthorized("Missing bearer token").into_response(),
+            );
+        };
+
+        let token = match ApiToken::authenticate(Database::global(), token.trim()).await {
+            Ok(Some(token)) => token,
+            Ok(None) => {
+                return ControlFlow::Break(
+                    ApiError::unauthorized("Invalid bearer token").into_response(),
+                );
+            }
+            Err(error) => return ControlFlow::Break(ApiError::from(error).into_response()),
+        };
+
+        if !token.permits(req.method().as_str()) {
+            return ControlFlow::Break(
+                ApiError::new(
+                    ApiStatusCode::MissingPrivileges,
+                    "The token's scope does not permit this request",
+                )
+                .into_response(),
+            );
+        }
+
+        req.extensions_mut().insert(token);
+        ControlFlow::Continue(req)
+    }
+}
@@ -0,0 +1,204 @@
+//! Middleware which rejects cross-site requests.
+
+use std::ops::ControlFlow;
+
+use galvyn::core::middleware::SimpleGalvynMiddleware;
+use galvyn::core::re_exports::axum::extract::Request;
+use galvyn::core::re_exports::axum::http::HeaderMap;
+use galvyn::core::re_exports::axum::http::Method;
+use galvyn::core::re_exports::axum::http::header;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use tracing::debug;
+
+/// Middleware which rejects cross-site requests.
+///
+/// The frontend is authenticated by the session cookie which browsers attach to
+/// requests of other sites as well. Requests which might change state are only
+/// passed on if the browser states that they originate from this site:
+/// - `Sec-Fetch-Site` has to be `same-origin` or `none` (i.e. typed by the user)
+/// - otherwise, the `Origin` has to match the `X-Forwarded-Host` (or `Host`) header
+///
+/// Requests without both headers don't come from a browser
+/// (e.g. back-channel logouts of an oidc provider) and are passed on.
+///
+/// Websocket handshakes are checked as well, because they are `GET`s carrying the cookie.
+#[derive(Copy, Clone, Debug)]
+pub struct CsrfProtectionLayer;
+
+impl SimpleGalvynMiddleware for CsrfProtectionLayer {
+    async fn pre_handler(&mut self, req: Request) -> ControlFlow<Response, Request> {
+        if is_safe(req.method(), req.headers()) || is_same_site(req.headers()) {
+            ControlFlow::Continue(req)
+        } else {
+            debug!(method = %req.method(), path = req.uri().path(), "Rejected cross-site request");
+            ControlFlow::Break(
+                ApiError::new(
+                    ApiStatusCode::MissingPrivileges,
+                    "Cross-site requests are not allowed",
+                )
+                .into_response(),
+            )
+        }
+    }
+}
+
+/// Check whether a request can't change any state
+fn is_safe(method: &Method, headers: &HeaderMap) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+        && !headers.contains_key(header::UPGRADE)
+}
+
+/// Check whether the browser states that a request originates from this site
+fn is_same_site(headers: &HeaderMap) -> bool {
+    if let Some(fetch_site) = headers.get("sec-fetch-site") {
+        return matches!(fetch_site.as_bytes(), b"same-origin" | b"none");
+    }
+
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let Some(host) = headers
+        .get("x-forwarded-host")
+        .or_else(|| headers.get(header::HOST))
+        .and_then(|host| host.to_str().ok())
+    else {
+        return false;
+    };
+    origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.split_once("://"))
+        .is_some_and(|(_scheme, origin_host)| origin_host.eq_ignore_ascii_case(host))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::ops::ControlFlow;
+
+    use galvyn::core::middleware::SimpleGalvynMiddleware;
+    use galvyn::core::re_exports::axum::body::Body;
+    use galvyn::core::re_exports::axum::extract::Request;
+    use galvyn::core::re_exports::axum::http::Method;
+
+    use super::CsrfProtectionLayer;
+
+    /// Passes a request with the headers through [`CsrfProtectionLayer`]
+    ///
+    /// # Returns
+    /// Whether the request reached the handler
+    async fn is_passed_on(method: Method, headers: &[(&str, &str)]) -> bool {
+        let request = headers
+            .iter()
+            .fold(
+                Request::builder().method(method).uri("/api/frontend/v1/"),
+                |request, &(name, value)| request.header(name, value),
+            )
+            .body(Body::empty())
+            .unwrap();
+        match CsrfProtectionLayer.pre_handler(request).await {
+            ControlFlow::Continue(_) => true,
+            ControlFlow::Break(_) => false,
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_cross_site_form_post() {
+        assert!(
+            !is_passed_on(
+                Method::POST,
+                &[
+                    ("host", "tavern.example"),
+                    ("origin", "https://evil.example"),
+                    ("sec-fetch-site", "cross-site"),
+                    ("content-type", "application/x-www-form-urlencoded"),
+                ],
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_mismatched_origin_without_sec_fetch_site() {
+        assert!(
+            !is_passed_on(
+                Method::POST,
+                &[
+                    ("host", "tavern.example"),
+                    ("origin", "https://evil.example"),
+                ],
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn accepts_same_origin_post() {
+        assert!(
+            is_passed_on(
+                Method::POST,
+                &[
+                    ("host", "tavern.example"),
+                    ("origin", "https://tavern.example"),
+                    ("sec-fetch-site", "same-origin"),
+                ],
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn accepts_matching_forwarded_host() {
+        assert!(
+            is_passed_on(
+                Method::POST,
+                &[
+                    ("host", "webserver:8080"),
+                    ("x-forwarded-host", "tavern.example"),
+                    ("origin", "https://tavern.example"),
+                ],
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn accepts_requests_without_browser_headers() {
+        assert!(is_passed_on(Method::POST, &[("host", "tavern.example")]).await);
+    }
+
+    #[tokio::test]
+    async fn rejects_cross_site_websocket_handshake() {
+        assert!(
+            !is_passed_on(
+                Method::GET,
+                &[
+                    ("host", "tavern.example"),
+                    ("origin", "https://evil.example"),
+                    ("sec-fetch-site", "cross-site"),
+                    ("connection", "upgrade"),
+                    ("upgrade", "websocket"),
+                ],
+            )
+            .await
+        );
+    }
+
+    #[tokio::test]
+    async fn accepts_cross_site_get() {
+        assert!(
+            is_passed_on(
+                Method::GET,
+                &[
+                    ("host", "tavern.example"),
+                    ("origin", "https://evil.example"),
+                    ("sec-fetch-site", "cross-site"),
+                ],
+            )
+            .await
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Middleware which records the requests of admins impersonating another account.
+
+use std::ops::ControlFlow;
+
+use galvyn::core::Module;
+use galvyn::core::middleware::SimpleGalvynMiddleware;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::extract::Request;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::rorm::Database;
+
+use crate::models::accounts::Account;
+use crate::models::impersonation_logs::ImpersonationLog;
+
+/// Middleware which records the requests of admins impersonating another account.
+///
+/// It should be wrapped by the
+/// [`AuthRequiredLayer`](crate::http::middlewares::auth_required::AuthRequiredLayer),
+/// which stops impersonations the admin is no longer allowed to perform.
+#[derive(Copy, Clone, Debug)]
+pub struct ImpersonationLogLayer;
+
+impl SimpleGalvynMiddleware for ImpersonationLogLayer {
+    async fn pre_handler(&mut self, req: Request) -> ControlFlow<Response, Request> {
+        let (mut parts, body) = req.into_parts();
+
+        let Ok(session) = Session::from_request_parts(&mut parts, &()).await else {
+            return ControlFlow::Break(
+                ApiError::server_error("Failed to load the session").into_response(),
+            );
+        };
+
+        let (admin, account) = match Account::get_impersonation(&session).await {
+            Ok(Some(impersonation)) => impersonation,
+            Ok(None) => return ControlFlow::Continue(Request::from_parts(parts, body)),
+            Err(error) => return ControlFlow::Break(error.into_response()),
+        };
+
+        if let Err(error) = ImpersonationLog::create(
+            Database::global(),
+            admin,
+            account,
+            parts.method.as_str(),
+            parts.uri.path(),
+        )
+        .await
+        {
+            return ControlFlow::Break(ApiError::from(error).into_response());
+        }
+
+        ControlFlow::Continue(Request::from_parts(parts, body))
+    }
+}
@@ -1,2 +1,7 @@
 //! Middlewares are defined in this module
+pub mod api_token_required;
 pub mod auth_required;
+pub mod csrf_protection;
+pub mod impersonation_log;
+pub mod oidc_provider_available;
+pub mod role_required;
@@ -0,0 +1,44 @@
+//! Middleware which rejects requests while the oidc provider they address is unavailable.
+
+use std::ops::ControlFlow;
+
+use galvyn::core::Module;
+use galvyn::core::middleware::SimpleGalvynMiddleware;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::re_exports::axum::extract::Request;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
+
+use crate::http::handler_frontend::oidc::schema::OidcProviderPath;
+use crate::modules::oidc::OidcProviderUnavailable;
+use crate::modules::oidc::OpenIdConnect;
+
+/// Middleware which rejects requests while the oidc provider they address is unavailable.
+///
+/// The provider could not be discovered yet (e.g. because it was unreachable during startup),
+/// so `503 Service Unavailable` is returned until the background discovery succeeds.
+///
+/// The wrapped routes have to contain a `{provider}` path parameter.
+#[derive(Copy, Clone, Debug)]
+pub struct OidcProviderAvailableLayer;
+
+impl SimpleGalvynMiddleware for OidcProviderAvailableLayer {
+    async fn pre_handler(&mut self, req: Request) -> ControlFlow<Response, Request> {
+        let (mut parts, body) = req.into_parts();
+
+        // Unknown providers are rejected by the handlers
+        let provider = Path::<OidcProviderPath>::from_request_parts(&mut parts, &())
+            .await
+            .ok()
+            .and_then(|Path(OidcProviderPath { provider })| {
+                OpenIdConnect::global().provider(&provider).ok()
+            });
+        match provider {
+            Some(provider) if !provider.is_available() => {
+                ControlFlow::Break(OidcProviderUnavailable.into_response())
+            }
+            _ => ControlFlow::Continue(Request::from_parts(parts, body)),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Middleware which requires the user to have been granted a role.
+
+use std::ops::ControlFlow;
+
+use galvyn::core::Module;
+use galvyn::core::middleware::SimpleGalvynMiddleware;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::extract::Request;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::schema::ApiStatusCode;
+use galvyn::rorm::Database;
+
+use crate::models::accounts::Account;
+use crate::models::accounts::AccountRole;
+
+/// Middleware which requires the user to have been granted a role.
+///
+/// It implies [`AuthRequiredLayer`](crate::http::middlewares::auth_required::AuthRequiredLayer).
+#[derive(Copy, Clone, Debug)]
+pub struct RoleRequiredLayer(pub AccountRole);
+
+impl SimpleGalvynMiddleware for RoleRequiredLayer {
+    async fn pre_handler(&mut self, req: Request) -> ControlFlow<Response, Request> {
+        let (mut parts, body) = req.into_parts();
+
+        let account = match Account::from_request_parts(&mut parts, &()).await {
+            Ok(account) => account,
+            Err(rejection) => return ControlFlow::Break(rejection.into_response()),
+        };
+
+        match account.has_role(Database::global(), self.0).await {
+            Ok(true) => ControlFlow::Continue(Request::from_parts(parts, body)),
+            Ok(false) => ControlFlow::Break(
+                ApiError::new(ApiStatusCode::MissingPrivileges, "Missing privileges")
+                    .into_response(),
+            ),
+            Err(error) => ControlFlow::Break(ApiError::from(error).into_response()),
+        }
+    }
+}
@@ -6,6 +6,7 @@ use galvyn::openapi::{OpenAPI, OpenapiRouterExt};
 use tower_http::trace::{DefaultMakeSpan, TraceLayer};
 use tracing::{Level, instrument};
 
+pub mod handler_api;
 pub mod handler_frontend;
 pub mod middlewares;
 
@@ -39,6 +40,9 @@ pub fn initialize_routes() -> GalvynRouter {
             "/api/frontend/v1",
             handler_frontend::initialize_routes().openapi_page(FrontendApi),
         )
-        //GalvynRouter::with_openapi_page(Api).layer(axum::middleware::from_fn(middlewares::auth_required::auth_required))
+        .nest(
+            "/api/v1",
+            handler_api::initialize_routes().openapi_page(Api),
+        )
         .layer(TraceLayer::new_for_http().make_span_with(DefaultMakeSpan::new().level(Level::INFO)))
 }
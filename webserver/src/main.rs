@@ -5,6 +5,7 @@
 use crate::rorm::cli::make_migrations;
 use clap::Parser;
 use galvyn::core::DatabaseSetup;
+use galvyn::core::Module;
 use galvyn::core::re_exports::rorm;
 use galvyn::rorm::config::DatabaseConfig;
 use galvyn::rorm::{Database, DatabaseConfiguration};
@@ -14,10 +15,18 @@ use std::net::SocketAddr;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
+use uuid::Uuid;
 
 use crate::cli::Cli;
 use crate::cli::Command;
 use crate::config::{DB, LISTEN_ADDRESS, LISTEN_PORT};
+use crate::models::accounts::Account;
+use crate::models::accounts::AccountRole;
+use crate::modules::event_bus::EventBus;
+use crate::modules::ldap::Ldap;
+use crate::modules::ldap::LdapConfig;
+use crate::modules::oidc::OpenIdConnect;
+use crate::utils::retention;
 
 mod cli;
 pub mod config;
@@ -27,12 +36,36 @@ pub mod modules;
 pub mod utils;
 
 async fn start() -> Result<(), Box<dyn Error>> {
-    Galvyn::builder(GalvynSetup::default())
+    let ldap_config = LdapConfig::from_env();
+    let ldap_enabled = ldap_config.is_some();
+
+    let mut builder = Galvyn::builder(GalvynSetup::default())
         .register_module::<Database>(DatabaseSetup::Custom(DatabaseConfiguration::new(
             DB.clone(),
         )))
-        .init_modules()
-        .await?
+        .register_module::<EventBus>(())
+        .register_module::<OpenIdConnect>(());
+    if let Some(ldap_config) = ldap_config {
+        builder = builder.register_module::<Ldap>(ldap_config);
+    }
+    let galvyn = builder.init_modules().await?;
+
+    if ldap_enabled {
+        Ldap::global().start_sync_task();
+    } else if let Some(provider) = OpenIdConnect::global()
+        .providers()
+        .find(|provider| provider.ldap)
+    {
+        return Err(format!(
+            "The oidc provider {} requires LDAP, but LDAP_URL is unset",
+            provider.name
+        )
+        .into());
+    }
+    OpenIdConnect::global().start_refresh_task();
+    retention::start_retention_task();
+
+    galvyn
         .add_routes(http::initialize_routes())
         .start(SocketAddr::from((
             *LISTEN_ADDRESS.get(),
@@ -43,6 +76,24 @@ async fn start() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+async fn grant_admin(uuid: Uuid) -> Result<(), Box<dyn Error>> {
+    let db = Database::connect(DatabaseConfiguration::new(DB.clone())).await?;
+    let mut tx = db.start_transaction().await?;
+
+    let Some(mut account) = Account::find_by_uuid(&mut tx, uuid).await? else {
+        return Err(format!("There is no account with the uuid {uuid}").into());
+    };
+    let mut roles = account.get_roles(&mut tx).await?;
+    roles.push(AccountRole::Admin);
+    account.set_roles(&mut tx, &roles).await?;
+
+    tx.commit().await?;
+    db.close().await;
+
+    println!("Granted the admin role to {}", account.display_name);
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     if let Err(errors) = config::load_env() {
@@ -61,6 +112,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     match cli.command {
         Command::Start => start().await?,
+        Command::GrantAdmin { account } => grant_admin(account).await?,
         #[cfg(debug_assertions)]
         Command::MakeMigrations { migrations_dir } => {
             use std::io::Write;
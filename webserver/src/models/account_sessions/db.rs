@@ -0,0 +1,51 @@
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+
+/// A login of an account through the frontend
+///
+/// Deleting a row logs the associated session out.
+#[derive(Debug, Model)]
+#[rorm(rename = "AccountSession")]
+pub struct AccountSessionModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The logged-in account
+    #[rorm(on_delete = "Cascade", on_update = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// The session id (`sid`) the oidc provider assigned to the login
+    pub oidc_sid: Option<MaxStr<255>>,
+
+    /// The point in time the login happened
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+
+    /// The point in time the session was last used
+    ///
+    /// This is updated at most every few minutes.
+    pub last_seen_at: Option<OffsetDateTime>,
+
+    /// The `User-Agent` of the browser which logged in
+    pub user_agent: Option<MaxStr<1024>>,
+
+    /// The ip address the login originated from
+    pub ip: Option<MaxStr<64>>,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "AccountSessionModel")]
+pub struct AccountSessionModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub oidc_sid: Option<MaxStr<255>>,
+    pub last_seen_at: Option<OffsetDateTime>,
+    pub user_agent: Option<MaxStr<1024>>,
+    pub ip: Option<MaxStr<64>>,
+}
@@ -0,0 +1,301 @@
+//! Account session model
+
+use std::convert::Infallible;
+
+use galvyn::core::handler::request_part::RequestPart;
+use galvyn::core::handler::request_part::ShouldBeRequestPart;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::http::header;
+use galvyn::core::re_exports::axum::http::request::Parts;
+use galvyn::core::re_exports::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::conditions::DynamicCollection;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::config::SESSION_LIFETIME_DAYS;
+use crate::models::account_sessions::db::AccountSessionModel;
+use crate::models::account_sessions::db::AccountSessionModelInsert;
+use crate::utils::max_str::truncate;
+
+pub(in crate::models) mod db;
+
+/// Minimum time between two updates of [`AccountSession::last_seen_at`]
+const LAST_SEEN_INTERVAL: Duration = Duration::minutes(5);
+
+/// A login of an account through the frontend
+///
+/// The [`Session`](galvyn::core::session::Session) only references this row,
+/// so that logins can be revoked without access to the session store.
+#[derive(Debug, Clone)]
+pub struct AccountSession {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The logged-in account
+    pub account: Uuid,
+
+    /// The session id (`sid`) the oidc provider assigned to the login
+    pub oidc_sid: Option<MaxStr<255>>,
+
+    /// The point in time the login happened
+    pub created_at: OffsetDateTime,
+
+    /// The point in time the session was last used
+    pub last_seen_at: Option<OffsetDateTime>,
+
+    /// The `User-Agent` of the browser which logged in
+    pub user_agent: Option<MaxStr<1024>>,
+
+    /// The ip address the login originated from
+    pub ip: Option<MaxStr<64>>,
+}
+
+/// Information about the client sending a request
+///
+/// The ip address is taken from the `X-Real-IP` header set by the reverse proxy.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    /// The client's `User-Agent`
+    pub user_agent: Option<MaxStr<1024>>,
+
+    /// The client's ip address
+    pub ip: Option<MaxStr<64>>,
+}
+
+impl AccountSession {
+    /// Create a new session for an account
+    #[instrument(name = "AccountSession::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        account: Uuid,
+        oidc_sid: Option<MaxStr<255>>,
+        client: ClientInfo,
+    ) -> anyhow::Result<AccountSession> {
+        let model = rorm::insert(exe, AccountSessionModel)
+            .single(&AccountSessionModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account),
+                oidc_sid,
+                last_seen_at: Some(OffsetDateTime::now_utc()),
+                user_agent: client.user_agent,
+                ip: client.ip,
+            })
+            .await?;
+        Ok(AccountSession::from(model))
+    }
+
+    /// Find a session of an account
+    ///
+    /// Expired sessions are not returned.
+    pub async fn find(
+        exe: impl Executor<'_>,
+        account: Uuid,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<AccountSession>> {
+        let session = rorm::query(exe, AccountSessionModel)
+            .condition(and![
+                AccountSessionModel.uuid.equals(uuid),
+                AccountSessionModel.account.equals(account)
+            ])
+            .optional()
+            .await?;
+        Ok(session
+            .map(AccountSession::from)
+            .filter(|session| !session.is_expired()))
+    }
+
+    /// Retrieve all sessions of an account
+    ///
+    /// Expired sessions are not returned.
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        account: Uuid,
+    ) -> anyhow::Result<Vec<AccountSession>> {
+        let sessions = rorm::query(exe, AccountSessionModel)
+            .condition(AccountSessionModel.account.equals(account))
+            .all()
+            .await?;
+        Ok(sessions
+            .into_iter()
+            .map(AccountSession::from)
+            .filter(|session| !session.is_expired())
+            .collect())
+    }
+
+    /// Whether the session was not used for longer than [`SESSION_LIFETIME_DAYS`]
+    pub fn is_expired(&self) -> bool {
+        let last_seen_at = self.last_seen_at.unwrap_or(self.created_at);
+        OffsetDateTime::now_utc() - last_seen_at > Duration::days(i64::from(*SESSION_LIFETIME_DAYS))
+    }
+
+    /// Update `last_seen_at` if it is older than a few minutes
+    ///
+    /// This avoids writing to the database on every request.
+    pub async fn touch(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let now = OffsetDateTime::now_utc();
+        if self
+            .last_seen_at
+            .is_some_and(|last_seen_at| now - last_seen_at < LAST_SEEN_INTERVAL)
+        {
+            return Ok(());
+        }
+
+        rorm::update(exe, AccountSessionModel)
+            .set(AccountSessionModel.last_seen_at, Some(now))
+            .condition(AccountSessionModel.uuid.equals(self.uuid))
+            .await?;
+        self.last_seen_at = Some(now);
+        Ok(())
+    }
+
+    /// Delete a session of an account
+    ///
+    /// # Returns
+    /// `false` if the account has no session with this uuid
+    #[instrument(name = "AccountSession::delete", skip(exe))]
+    pub async fn delete(exe: impl Executor<'_>, account: Uuid, uuid: Uuid) -> anyhow::Result<bool> {
+        let deleted = rorm::delete(exe, AccountSessionModel)
+            .condition(and![
+                AccountSessionModel.uuid.equals(uuid),
+                AccountSessionModel.account.equals(account)
+            ])
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete all sessions of an account
+    ///
+    /// The session `except` is kept (e.g. the one revoking the others).
+    ///
+    /// # Returns
+    /// The uuids of the deleted sessions
+    #[instrument(name = "AccountSession::delete_all_by_account", skip(exe))]
+    pub async fn delete_all_by_account(
+        exe: impl Executor<'_>,
+        account: Uuid,
+        except: Option<Uuid>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        Self::delete_matching(exe, account, |session| Some(session.uuid) != except).await
+    }
+
+    /// Delete the sessions of an account which were created through a login with the oidc provider
+    ///
+    /// If `oidc_sid` is `None`, all sessions of the account are deleted.
+    ///
+    /// # Returns
+    /// The uuids of the deleted sessions
+    #[instrument(name = "AccountSession::delete_by_oidc_sid", skip(exe))]
+    pub async fn delete_by_oidc_sid(
+        exe: impl Executor<'_>,
+        account: Uuid,
+        oidc_sid: Option<&str>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        Self::delete_matching(exe, account, |session| match oidc_sid {
+            Some(oidc_sid) => session.oidc_sid.as_deref() == Some(oidc_sid),
+            None => true,
+        })
+        .await
+    }
+
+    /// Delete the sessions of all accounts which expired
+    ///
+    /// # Returns
+    /// The number of deleted sessions
+    #[instrument(name = "AccountSession::delete_expired", skip(exe))]
+    pub async fn delete_expired(exe: impl Executor<'_>) -> anyhow::Result<usize> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let expired: Vec<Uuid> = rorm::query(guard.get_transaction(), AccountSessionModel)
+            .all()
+            .await?
+            .into_iter()
+            .map(AccountSession::from)
+            .filter(AccountSession::is_expired)
+            .map(|session| session.uuid)
+            .collect();
+        if !expired.is_empty() {
+            rorm::delete(guard.get_transaction(), AccountSessionModel)
+                .condition(DynamicCollection::or(
+                    expired
+                        .iter()
+                        .map(|uuid| AccountSessionModel.uuid.equals(*uuid))
+                        .collect(),
+                ))
+                .await?;
+        }
+
+        guard.commit().await?;
+        Ok(expired.len())
+    }
+
+    /// Delete the sessions of an account matching a predicate
+    ///
+    /// The uuids are returned to notify the sessions' websockets.
+    async fn delete_matching(
+        exe: impl Executor<'_>,
+        account: Uuid,
+        predicate: impl Fn(&AccountSession) -> bool,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let deleted: Vec<Uuid> = Self::find_all_by_account(guard.get_transaction(), account)
+            .await?
+            .into_iter()
+            .filter(&predicate)
+            .map(|session| session.uuid)
+            .collect();
+        if !deleted.is_empty() {
+            rorm::delete(guard.get_transaction(), AccountSessionModel)
+                .condition(DynamicCollection::or(
+                    deleted
+                        .iter()
+                        .map(|uuid| AccountSessionModel.uuid.equals(*uuid))
+                        .collect(),
+                ))
+                .await?;
+        }
+
+        guard.commit().await?;
+        Ok(deleted)
+    }
+}
+
+impl From<AccountSessionModel> for AccountSession {
+    fn from(value: AccountSessionModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            account: value.account.0,
+            oidc_sid: value.oidc_sid,
+            created_at: value.created_at,
+            last_seen_at: value.last_seen_at,
+            user_agent: value.user_agent,
+            ip: value.ip,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ClientInfo {
+            user_agent: header_value(parts, header::USER_AGENT.as_str()),
+            ip: header_value(parts, "x-real-ip"),
+        })
+    }
+}
+
+impl ShouldBeRequestPart for ClientInfo {}
+impl RequestPart for ClientInfo {}
+
+/// Reads a header into a [`MaxStr`], cutting off everything exceeding its length
+fn header_value<const N: usize>(parts: &Parts, name: &str) -> Option<MaxStr<N>> {
+    let value = parts.headers.get(name)?.to_str().ok()?;
+    truncate(value).ok()
+}
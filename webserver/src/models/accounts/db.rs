@@ -1,7 +1,11 @@
 use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
 use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::models::accounts::AccountRole;
+
 /// An account for logging into this admin platform
 #[derive(Debug, Model)]
 #[rorm(rename = "Account")]
@@ -13,16 +17,50 @@ pub struct AccountModel {
     /// The name that is used for displaying purposes
     pub display_name: MaxStr<255>,
 
+    /// The email address provided by the oidc provider
+    pub email: Option<MaxStr<255>>,
+
     /// DN (distinguished name) for LDAP
+    ///
+    /// This is `None` until the account has been matched against the directory.
     #[rorm(unique)]
-    pub ldap_dn: MaxStr<2048>,
+    pub ldap_dn: Option<MaxStr<2048>>,
 
     /// Current balance of the user (i.e., what he/she owes to the community)
     pub balance: i64,
 
     /// Subject for OIDC
-    #[rorm(unique)]
-    pub sub: MaxStr<255>,
+    ///
+    /// Subjects are only unique per issuer.
+    /// This is enforced by the `Account_issuer_sub_key` index created in a raw migration.
+    /// It is `None` for guests, who don't log in through an oidc provider.
+    pub sub: Option<MaxStr<255>>,
+
+    /// Issuer of the OIDC provider the subject belongs to
+    ///
+    /// This is empty for accounts created before multiple providers were supported.
+    /// They are adopted by the first directory-backed provider their subject logs in with.
+    /// It is `None` for guests, who don't log in through an oidc provider.
+    pub issuer: Option<MaxStr<255>>,
+
+    /// Set by the LDAP synchronisation when `ldap_dn` could no longer be found in the directory
+    #[rorm(default = false)]
+    pub ldap_dn_missing: bool,
+
+    /// The point in time the account was deactivated
+    ///
+    /// Deactivated accounts can't log in but are kept to preserve their history.
+    pub deactivated_at: Option<OffsetDateTime>,
+
+    /// The member who sponsors this guest account
+    ///
+    /// This is `None` for members, who log in through an oidc provider.
+    #[rorm(on_delete = "SetNull", on_update = "Cascade")]
+    pub sponsor: Option<ForeignModel<AccountModel>>,
+
+    /// Whether charges of this guest account are billed to its sponsor
+    #[rorm(default = false)]
+    pub bill_sponsor: bool,
 }
 
 #[derive(Debug, Patch)]
@@ -30,7 +68,69 @@ pub struct AccountModel {
 pub struct AccountModelInsert {
     pub uuid: Uuid,
     pub display_name: MaxStr<255>,
-    pub ldap_dn: MaxStr<2048>,
+    pub email: Option<MaxStr<255>>,
+    pub ldap_dn: Option<MaxStr<2048>>,
     pub balance: i64,
-    pub sub: MaxStr<255>,
+    pub sub: Option<MaxStr<255>>,
+    pub issuer: Option<MaxStr<255>>,
+    pub deactivated_at: Option<OffsetDateTime>,
+    pub sponsor: Option<ForeignModel<AccountModel>>,
+    pub bill_sponsor: bool,
+}
+
+/// A role granted to an account
+#[derive(Debug, Model)]
+#[rorm(rename = "AccountRole")]
+pub struct AccountRoleModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The account the role is granted to
+    #[rorm(on_delete = "Cascade", on_update = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// The granted role
+    pub role: AccountRole,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "AccountRoleModel")]
+pub struct AccountRoleModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub role: AccountRole,
+}
+
+/// A balance which has been written off by a treasurer
+#[derive(Debug, Model)]
+#[rorm(rename = "BalanceWriteOff")]
+pub struct BalanceWriteOffModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The account whose balance has been written off
+    #[rorm(on_delete = "Restrict", on_update = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// The treasurer who wrote off the balance
+    #[rorm(on_delete = "Restrict", on_update = "Cascade")]
+    pub treasurer: ForeignModel<AccountModel>,
+
+    /// The balance the account had before it was written off
+    pub amount: i64,
+
+    /// The point in time the balance was written off
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "BalanceWriteOffModel")]
+pub struct BalanceWriteOffModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub treasurer: ForeignModel<AccountModel>,
+    pub amount: i64,
 }
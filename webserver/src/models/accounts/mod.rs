@@ -1,16 +1,45 @@
 //! Account model
 
+use galvyn::core::Module;
+use galvyn::core::handler::request_part::RequestPart;
+use galvyn::core::handler::request_part::ShouldBeRequestPart;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::http::request::Parts;
 use galvyn::core::re_exports::rorm;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
 use galvyn::core::session::Session;
 use galvyn::core::stuff::api_error::{ApiError, ApiResult};
+use galvyn::rorm::Database;
+use galvyn::rorm::DbEnum;
+use galvyn::rorm::and;
+use galvyn::rorm::conditions::DynamicCollection;
 use galvyn::rorm::db::Executor;
+use galvyn::rorm::db::executor::Nothing;
+use galvyn::rorm::db::executor::One;
+use galvyn::rorm::db::sql::value::Value;
 use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::or;
+use galvyn::rorm::prelude::ForeignModelByField;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::ops::Deref;
+use time::OffsetDateTime;
 use tracing::instrument;
 use tracing::log::warn;
 use uuid::Uuid;
 
+use crate::models::account_sessions::AccountSession;
+use crate::models::account_sessions::ClientInfo;
 use crate::models::accounts::db::AccountModel;
+use crate::models::accounts::db::AccountModelInsert;
+use crate::models::accounts::db::AccountRoleModel;
+use crate::models::accounts::db::AccountRoleModelInsert;
+use crate::models::accounts::db::BalanceWriteOffModel;
+use crate::models::accounts::db::BalanceWriteOffModelInsert;
+use crate::models::api_tokens::ApiToken;
+use crate::modules::event_bus::EventBus;
 
 pub(in crate::models) mod db;
 
@@ -22,17 +51,67 @@ pub struct Account {
     /// The name that is used for displaying purposes
     pub display_name: MaxStr<255>,
 
+    /// The email address provided by the oidc provider
+    pub email: Option<MaxStr<255>>,
+
     /// DN (distinguished name) for LDAP
-    pub ldap_dn: MaxStr<2048>,
+    pub ldap_dn: Option<MaxStr<2048>>,
 
     /// Current balance of the user (i.e., what he/she owes to the community)
     pub balance: i64,
 
     /// Subject for OIDC
-    pub sub: MaxStr<255>,
+    ///
+    /// This is `None` for guests.
+    pub sub: Option<MaxStr<255>>,
+
+    /// Issuer of the OIDC provider the subject belongs to
+    ///
+    /// This is `None` for guests.
+    pub issuer: Option<MaxStr<255>>,
+
+    /// The `ldap_dn` could no longer be found in the directory
+    pub ldap_dn_missing: bool,
+
+    /// The point in time the account was deactivated
+    pub deactivated_at: Option<OffsetDateTime>,
+
+    /// The member sponsoring this guest account
+    pub sponsor: Option<Uuid>,
+
+    /// Whether charges of this guest account are billed to its sponsor
+    pub bill_sponsor: bool,
+}
+
+/// A role granting an account additional privileges
+#[derive(
+    DbEnum,
+    Debug,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+pub enum AccountRole {
+    /// A regular member of the community
+    Member,
+    /// May organize dinners
+    Cook,
+    /// May adjust the balances of accounts
+    Treasurer,
+    /// May manage accounts
+    Admin,
 }
 
 const SESSION_KEY: &str = "current_account_uuid";
+const SESSION_KEY_ACCOUNT_SESSION: &str = "current_account_session_uuid";
+const SESSION_KEY_IMPERSONATED: &str = "impersonated_account_uuid";
 
 impl Account {
     /// Update the display name of the current account
@@ -43,37 +122,561 @@ impl Account {
         display_name: MaxStr<255>,
     ) -> anyhow::Result<()> {
         rorm::update(exe, AccountModel)
-            .set(AccountModel.display_name, display_name)
+            .set(AccountModel.display_name, display_name.clone())
             .condition(AccountModel.uuid.equals(self.uuid))
             .await?;
+        self.display_name = display_name;
         Ok(())
     }
 
-    /// Find an account by its subject
-    pub async fn find_by_subject(
+    /// Update the email address of the current account
+    #[instrument(name = "Account::set_email", skip(self, exe))]
+    pub async fn set_email(
+        &mut self,
         exe: impl Executor<'_>,
-        sub: &MaxStr<255>,
+        email: Option<MaxStr<255>>,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.email, email.clone())
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        self.email = email;
+        Ok(())
+    }
+
+    /// Update the LDAP DN of the current account
+    ///
+    /// This also clears the [`Account::ldap_dn_missing`] flag.
+    #[instrument(name = "Account::set_ldap_dn", skip(self, exe))]
+    pub async fn set_ldap_dn(
+        &mut self,
+        exe: impl Executor<'_>,
+        ldap_dn: MaxStr<2048>,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.ldap_dn, Some(ldap_dn.clone()))
+            .set(AccountModel.ldap_dn_missing, false)
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        self.ldap_dn = Some(ldap_dn);
+        self.ldap_dn_missing = false;
+        Ok(())
+    }
+
+    /// Flag whether the LDAP DN of the current account could be found in the directory
+    #[instrument(name = "Account::set_ldap_dn_missing", skip(self, exe))]
+    pub async fn set_ldap_dn_missing(
+        &mut self,
+        exe: impl Executor<'_>,
+        ldap_dn_missing: bool,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.ldap_dn_missing, ldap_dn_missing)
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        self.ldap_dn_missing = ldap_dn_missing;
+        Ok(())
+    }
+
+    /// Create a new account for an OIDC subject
+    ///
+    /// The account starts with a balance of `0`.
+    /// `ldap_dn` is `None` for providers whose users are not part of the directory.
+    #[instrument(name = "Account::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        issuer: MaxStr<255>,
+        sub: MaxStr<255>,
+        display_name: MaxStr<255>,
+        email: Option<MaxStr<255>>,
+        ldap_dn: Option<MaxStr<2048>>,
+    ) -> anyhow::Result<Account> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let model = rorm::insert(guard.get_transaction(), AccountModel)
+            .single(&AccountModelInsert {
+                uuid: Uuid::new_v4(),
+                display_name,
+                email,
+                ldap_dn,
+                balance: 0,
+                sub: Some(sub),
+                issuer: Some(issuer),
+                deactivated_at: None,
+                sponsor: None,
+                bill_sponsor: false,
+            })
+            .await?;
+        let mut account = Account::from(model);
+        account
+            .set_roles(guard.get_transaction(), &[AccountRole::Member])
+            .await?;
+
+        guard.commit().await?;
+        Ok(account)
+    }
+
+    /// Create a new guest account sponsored by a member
+    ///
+    /// Guests don't have an oidc subject and aren't granted any roles.
+    /// They log in through links created by their sponsor.
+    #[instrument(name = "Account::create_guest", skip(exe))]
+    pub async fn create_guest(
+        exe: impl Executor<'_>,
+        sponsor: Uuid,
+        display_name: MaxStr<255>,
+        email: Option<MaxStr<255>>,
+        bill_sponsor: bool,
+    ) -> anyhow::Result<Account> {
+        let model = rorm::insert(exe, AccountModel)
+            .single(&AccountModelInsert {
+                uuid: Uuid::new_v4(),
+                display_name,
+                email,
+                ldap_dn: None,
+                balance: 0,
+                sub: None,
+                issuer: None,
+                deactivated_at: None,
+                sponsor: Some(ForeignModelByField(sponsor)),
+                bill_sponsor,
+            })
+            .await?;
+        Ok(Account::from(model))
+    }
+
+    /// Retrieve the guest accounts sponsored by a member
+    pub async fn find_guests_by_sponsor(
+        exe: impl Executor<'_>,
+        sponsor: Uuid,
+    ) -> anyhow::Result<Vec<Account>> {
+        let guests = rorm::query(exe, AccountModel)
+            .condition(AccountModel.sponsor.equals(sponsor))
+            .all()
+            .await?;
+        Ok(guests.into_iter().map(Account::from).collect())
+    }
+
+    /// Update whether charges of the current guest account are billed to its sponsor
+    #[instrument(name = "Account::set_bill_sponsor", skip(self, exe))]
+    pub async fn set_bill_sponsor(
+        &mut self,
+        exe: impl Executor<'_>,
+        bill_sponsor: bool,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.bill_sponsor, bill_sponsor)
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        self.bill_sponsor = bill_sponsor;
+        Ok(())
+    }
+
+    /// Check whether the current account is a guest sponsored by a member
+    pub fn is_guest(&self) -> bool {
+        self.sponsor.is_some()
+    }
+
+    /// The account charges of the current account are billed to
+    ///
+    /// This is the sponsor for guests billing their sponsor and the account itself otherwise.
+    pub fn billed_account(&self) -> Uuid {
+        match self.sponsor {
+            Some(sponsor) if self.bill_sponsor => sponsor,
+            _ => self.uuid,
+        }
+    }
+
+    /// Retrieve all accounts
+    pub async fn find_all(exe: impl Executor<'_>) -> anyhow::Result<Vec<Account>> {
+        let accounts = rorm::query(exe, AccountModel).all().await?;
+        Ok(accounts.into_iter().map(Account::from).collect())
+    }
+
+    /// Retrieve the accounts with the given primary keys
+    pub async fn find_all_by_uuids(
+        exe: impl Executor<'_>,
+        uuids: &[Uuid],
+    ) -> anyhow::Result<Vec<Account>> {
+        if uuids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let accounts = rorm::query(exe, AccountModel)
+            .condition(DynamicCollection::or(
+                uuids
+                    .iter()
+                    .map(|uuid| AccountModel.uuid.equals(*uuid))
+                    .collect(),
+            ))
+            .all()
+            .await?;
+        Ok(accounts.into_iter().map(Account::from).collect())
+    }
+
+    /// Retrieve the roles granted to the current account
+    pub async fn get_roles(&self, exe: impl Executor<'_>) -> anyhow::Result<Vec<AccountRole>> {
+        let roles = rorm::query(exe, AccountRoleModel.role)
+            .condition(AccountRoleModel.account.equals(self.uuid))
+            .all()
+            .await?;
+        Ok(roles)
+    }
+
+    /// Retrieve the roles of all accounts at once
+    pub async fn get_all_roles(
+        exe: impl Executor<'_>,
+    ) -> anyhow::Result<HashMap<Uuid, Vec<AccountRole>>> {
+        let mut roles: HashMap<Uuid, Vec<AccountRole>> = HashMap::new();
+        for (account, role) in rorm::query(exe, (AccountRoleModel.account, AccountRoleModel.role))
+            .all()
+            .await?
+        {
+            roles.entry(account.0).or_default().push(role);
+        }
+        Ok(roles)
+    }
+
+    /// Check whether the current account has been granted a role
+    pub async fn has_role(
+        &self,
+        exe: impl Executor<'_>,
+        role: AccountRole,
+    ) -> anyhow::Result<bool> {
+        let role = rorm::query(exe, AccountRoleModel.uuid)
+            .condition(and![
+                AccountRoleModel.account.equals(self.uuid),
+                AccountRoleModel.role.equals(role)
+            ])
+            .optional()
+            .await?;
+        Ok(role.is_some())
+    }
+
+    /// Replace the roles granted to the current account
+    #[instrument(name = "Account::set_roles", skip(self, exe))]
+    pub async fn set_roles(
+        &mut self,
+        exe: impl Executor<'_>,
+        roles: &[AccountRole],
+    ) -> anyhow::Result<()> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        rorm::delete(guard.get_transaction(), AccountRoleModel)
+            .condition(AccountRoleModel.account.equals(self.uuid))
+            .await?;
+
+        let mut unique_roles = roles.to_vec();
+        unique_roles.sort();
+        unique_roles.dedup();
+        rorm::insert(guard.get_transaction(), AccountRoleModel)
+            .return_nothing()
+            .bulk(unique_roles.into_iter().map(|role| AccountRoleModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(self.uuid),
+                role,
+            }))
+            .await?;
+
+        guard.commit().await?;
+        Ok(())
+    }
+
+    /// Add `amount` to the balance of the current account
+    ///
+    /// The addition is done by the database, so concurrent adjustments can't overwrite each other.
+    /// An overflow is rejected by the database.
+    #[instrument(name = "Account::adjust_balance", skip(self, exe))]
+    pub async fn adjust_balance(
+        &mut self,
+        exe: impl Executor<'_>,
+        amount: i64,
+    ) -> anyhow::Result<()> {
+        let row = exe
+            .execute::<One>(
+                r#"UPDATE "Account" SET "balance" = "balance" + $1 WHERE "uuid" = $2 RETURNING "balance";"#
+                    .to_string(),
+                vec![Value::I64(amount), Value::Uuid(self.uuid)],
+            )
+            .await?;
+
+        self.balance = row.get(0)?;
+        Ok(())
+    }
+
+    /// Set the balance of the current account to `0`
+    ///
+    /// The written off balance is recorded together with the `treasurer` responsible for it.
+    ///
+    /// # Returns
+    /// The balance which has been written off
+    #[instrument(name = "Account::write_off_balance", skip(self, exe))]
+    pub async fn write_off_balance(
+        &mut self,
+        exe: impl Executor<'_>,
+        treasurer: Uuid,
+    ) -> anyhow::Result<i64> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        // The lock keeps concurrent adjustments from changing the balance before it is reset
+        let row = guard
+            .get_transaction()
+            .execute::<One>(
+                r#"SELECT "balance" FROM "Account" WHERE "uuid" = $1 FOR UPDATE;"#.to_string(),
+                vec![Value::Uuid(self.uuid)],
+            )
+            .await?;
+        let balance: i64 = row.get(0)?;
+        rorm::update(guard.get_transaction(), AccountModel)
+            .set(AccountModel.balance, 0)
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        rorm::insert(guard.get_transaction(), BalanceWriteOffModel)
+            .return_nothing()
+            .single(&BalanceWriteOffModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(self.uuid),
+                treasurer: ForeignModelByField(treasurer),
+                amount: balance,
+            })
+            .await?;
+
+        guard.commit().await?;
+        self.balance = 0;
+        Ok(balance)
+    }
+
+    /// Deactivate the current account
+    ///
+    /// Deactivated accounts can't log in anymore, so all their sessions are ended.
+    /// The account itself is kept to preserve its history.
+    ///
+    /// # Returns
+    /// The uuids of the ended sessions
+    #[instrument(name = "Account::deactivate", skip(self, exe))]
+    pub async fn deactivate(&mut self, exe: impl Executor<'_>) -> anyhow::Result<Vec<Uuid>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let now = OffsetDateTime::now_utc();
+        rorm::update(guard.get_transaction(), AccountModel)
+            .set(AccountModel.deactivated_at, Some(now))
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        let ended =
+            AccountSession::delete_all_by_account(guard.get_transaction(), self.uuid, None).await?;
+
+        guard.commit().await?;
+        self.deactivated_at = Some(now);
+        Ok(ended)
+    }
+
+    /// Reactivate the current account
+    #[instrument(name = "Account::reactivate", skip(self, exe))]
+    pub async fn reactivate(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.deactivated_at, None)
+            .condition(AccountModel.uuid.equals(self.uuid))
+            .await?;
+        self.deactivated_at = None;
+        Ok(())
+    }
+
+    /// Check whether the current account has been deactivated
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivated_at.is_some()
+    }
+
+    /// Retrieve all accounts which are linked to a LDAP DN
+    pub async fn find_all_in_directory(exe: impl Executor<'_>) -> anyhow::Result<Vec<Account>> {
+        let accounts = rorm::query(exe, AccountModel)
+            .condition(AccountModel.ldap_dn.is_some())
+            .all()
+            .await?;
+        Ok(accounts.into_iter().map(Account::from).collect())
+    }
+
+    /// Find an account by its primary key
+    pub async fn find_by_uuid(
+        exe: impl Executor<'_>,
+        uuid: Uuid,
     ) -> anyhow::Result<Option<Account>> {
         let account = rorm::query(exe, AccountModel)
-            .condition(AccountModel.sub.equals(sub.deref()))
+            .condition(AccountModel.uuid.equals(uuid))
             .optional()
             .await?;
         Ok(account.map(Account::from))
     }
 
-    pub async fn set_logged_in(&mut self, session: &Session) -> ApiResult<()> {
+    /// Lock the account's row until the end of the transaction
+    ///
+    /// Concurrent transactions locking or adjusting the balance of the same account
+    /// wait for this one to finish.
+    pub async fn lock(exe: impl Executor<'_>, uuid: Uuid) -> anyhow::Result<()> {
+        exe.execute::<Nothing>(
+            r#"SELECT "uuid" FROM "Account" WHERE "uuid" = $1 FOR UPDATE;"#.to_string(),
+            vec![Value::Uuid(uuid)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Find an account by its OIDC issuer and subject
+    ///
+    /// Accounts created before multiple providers were supported don't have an issuer.
+    /// If `adopt_legacy` is set, such an account is adopted by the issuer presenting its subject.
+    /// This must only be set for providers backed by the directory,
+    /// because the legacy accounts all came from the directory's provider.
+    /// Guests are never found, because they don't have a subject.
+    #[instrument(name = "Account::find_by_subject", skip(exe))]
+    pub async fn find_by_subject(
+        exe: impl Executor<'_>,
+        issuer: &MaxStr<255>,
+        sub: &MaxStr<255>,
+        adopt_legacy: bool,
+    ) -> anyhow::Result<Option<Account>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let candidates = rorm::query(guard.get_transaction(), AccountModel)
+            .condition(and![
+                AccountModel.sub.equals(sub.deref()),
+                or![
+                    AccountModel.issuer.equals(issuer.deref()),
+                    AccountModel.issuer.equals("")
+                ]
+            ])
+            .all()
+            .await?;
+
+        let mut legacy = None;
+        for candidate in candidates {
+            if candidate.issuer.as_deref() == Some(issuer.deref()) {
+                guard.commit().await?;
+                return Ok(Some(Account::from(candidate)));
+            }
+            legacy = Some(candidate);
+        }
+
+        let Some(mut account) = legacy.filter(|_| adopt_legacy).map(Account::from) else {
+            guard.commit().await?;
+            return Ok(None);
+        };
+
+        rorm::update(guard.get_transaction(), AccountModel)
+            .set(AccountModel.issuer, Some(issuer.clone()))
+            .condition(AccountModel.uuid.equals(account.uuid))
+            .await?;
+        account.issuer = Some(issuer.clone());
+
+        guard.commit().await?;
+        Ok(Some(account))
+    }
+
+    /// Store this account as the logged-in one in the session
+    ///
+    /// `oidc_sid` is the session id assigned by the oidc provider, if it provided one.
+    /// It is used to match back-channel logouts to the session.
+    pub async fn set_logged_in(
+        &mut self,
+        exe: impl Executor<'_>,
+        session: &Session,
+        oidc_sid: Option<MaxStr<255>>,
+        client: ClientInfo,
+    ) -> ApiResult<()> {
+        let account_session = AccountSession::create(exe, self.uuid, oidc_sid, client).await?;
         session
             .insert(SESSION_KEY, self.uuid)
             .await
             .map_err(ApiError::map_server_error("Failed to write to session"))?;
+        session
+            .insert(SESSION_KEY_ACCOUNT_SESSION, account_session.uuid)
+            .await
+            .map_err(ApiError::map_server_error("Failed to write to session"))?;
+        Ok(())
+    }
+
+    /// Retrieve the uuid of the [`AccountSession`] stored in the session
+    pub async fn get_account_session(session: &Session) -> ApiResult<Option<Uuid>> {
+        Ok(session.get::<Uuid>(SESSION_KEY_ACCOUNT_SESSION).await?)
+    }
+
+    /// Start impersonating another account
+    ///
+    /// Until the impersonation is stopped, the [`Account`] extractor returns the impersonated account
+    /// instead of the logged-in one.
+    pub async fn start_impersonation(session: &Session, account: Uuid) -> ApiResult<()> {
+        session
+            .insert(SESSION_KEY_IMPERSONATED, account)
+            .await
+            .map_err(ApiError::map_server_error("Failed to write to session"))?;
         Ok(())
     }
 
+    /// Stop impersonating another account
+    ///
+    /// # Returns
+    /// The uuid of the account which was impersonated
+    pub async fn stop_impersonation(session: &Session) -> ApiResult<Option<Uuid>> {
+        Ok(session.remove::<Uuid>(SESSION_KEY_IMPERSONATED).await?)
+    }
+
+    /// Retrieve the ongoing impersonation stored in the session
+    ///
+    /// # Returns
+    /// The uuids of the impersonating admin and the impersonated account
+    pub async fn get_impersonation(session: &Session) -> ApiResult<Option<(Uuid, Uuid)>> {
+        let Some(account) = session.get::<Uuid>(SESSION_KEY_IMPERSONATED).await? else {
+            return Ok(None);
+        };
+        Ok(session
+            .get::<Uuid>(SESSION_KEY)
+            .await?
+            .map(|admin| (admin, account)))
+    }
+
+    /// Replace the logged-in account by the account it impersonates
+    ///
+    /// The impersonation is stopped if the logged-in account lost the `Admin` role
+    /// or the impersonated account has been deactivated.
+    async fn resolve_impersonation(
+        self,
+        exe: impl Executor<'_>,
+        session: &Session,
+    ) -> ApiResult<Account> {
+        let Some(impersonated) = session.get::<Uuid>(SESSION_KEY_IMPERSONATED).await? else {
+            return Ok(self);
+        };
+
+        let mut guard = exe.ensure_transaction().await?;
+        let account = if self
+            .has_role(guard.get_transaction(), AccountRole::Admin)
+            .await?
+        {
+            Account::find_by_uuid(guard.get_transaction(), impersonated)
+                .await?
+                .filter(|account| !account.is_deactivated())
+        } else {
+            None
+        };
+        guard.commit().await?;
+
+        match account {
+            Some(account) => Ok(account),
+            None => {
+                session.remove::<Uuid>(SESSION_KEY_IMPERSONATED).await?;
+                Ok(self)
+            }
+        }
+    }
+
+    /// Remove the logged-in account from the session
     pub async fn set_logged_out(session: &Session) -> ApiResult<()> {
-        if let Some(_account_uuid) = session.remove::<Uuid>(SESSION_KEY).await? {
-            if let Some(_session_id) = session.id() {
-                // TODO: notify websocket
-            } else {
+        session.remove::<Uuid>(SESSION_KEY_IMPERSONATED).await?;
+        let account_session = session.remove::<Uuid>(SESSION_KEY_ACCOUNT_SESSION).await?;
+        if let Some(account_uuid) = session.remove::<Uuid>(SESSION_KEY).await? {
+            if let Some(account_session) = account_session {
+                AccountSession::delete(Database::global(), account_uuid, account_session).await?;
+                EventBus::global().publish_logged_out([account_session]);
+            }
+            if session.id().is_none() {
                 warn!("A session with data should have an id!");
             }
         }
@@ -81,14 +684,86 @@ impl Account {
     }
 }
 
+impl<S: Send + Sync> FromRequestParts<S> for Account {
+    type Rejection = ApiError;
+
+    /// Load the account which is authenticated by the request
+    ///
+    /// This is either the account of an [`ApiToken`] validated by the
+    /// [`ApiTokenRequiredLayer`](crate::http::middlewares::api_token_required::ApiTokenRequiredLayer)
+    /// or the one logged in the request's session.
+    /// If the logged-in admin impersonates another account, the impersonated account is returned.
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(token) = parts.extensions.get::<ApiToken>() {
+            return Account::find_by_uuid(Database::global(), token.account)
+                .await?
+                .filter(|account| !account.is_deactivated())
+                .ok_or(ApiError::unauthorized("Invalid bearer token"));
+        }
+
+        let session = Session::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::server_error("Failed to load the session"))?;
+
+        let account_uuid = session
+            .get::<Uuid>(SESSION_KEY)
+            .await?
+            .ok_or(ApiError::unauthorized("Not logged in"))?;
+        let account_session = session.get::<Uuid>(SESSION_KEY_ACCOUNT_SESSION).await?;
+
+        let mut tx = Database::global().start_transaction().await?;
+
+        // The login might have been revoked (e.g. by a back-channel logout)
+        let account_session = match account_session {
+            Some(account_session) => {
+                AccountSession::find(&mut tx, account_uuid, account_session).await?
+            }
+            None => None,
+        };
+        let account = match account_session {
+            Some(mut account_session) => {
+                account_session.touch(&mut tx).await?;
+                Account::find_by_uuid(&mut tx, account_uuid).await?
+            }
+            None => None,
+        };
+        let account = match account.filter(|account| !account.is_deactivated()) {
+            Some(account) => Some(account.resolve_impersonation(&mut tx, &session).await?),
+            None => None,
+        };
+
+        tx.commit().await?;
+
+        match account {
+            Some(account) => Ok(account),
+            None => {
+                // The login was revoked or the account was deleted or deactivated since the login
+                session.remove::<Uuid>(SESSION_KEY).await?;
+                session.remove::<Uuid>(SESSION_KEY_ACCOUNT_SESSION).await?;
+                session.remove::<Uuid>(SESSION_KEY_IMPERSONATED).await?;
+                Err(ApiError::unauthorized("Not logged in"))
+            }
+        }
+    }
+}
+
+impl ShouldBeRequestPart for Account {}
+impl RequestPart for Account {}
+
 impl From<AccountModel> for Account {
     fn from(value: AccountModel) -> Self {
         Self {
             uuid: value.uuid,
             display_name: value.display_name,
+            email: value.email,
             ldap_dn: value.ldap_dn,
             balance: value.balance,
             sub: value.sub,
+            issuer: value.issuer,
+            ldap_dn_missing: value.ldap_dn_missing,
+            deactivated_at: value.deactivated_at,
+            sponsor: value.sponsor.map(|sponsor| sponsor.0),
+            bill_sponsor: value.bill_sponsor,
         }
     }
 }
@@ -0,0 +1,53 @@
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+use crate::models::api_tokens::ApiTokenScope;
+
+/// A token authenticating an account against the API
+#[derive(Debug, Model)]
+#[rorm(rename = "ApiToken")]
+pub struct ApiTokenModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The account the token authenticates as
+    #[rorm(on_delete = "Cascade", on_update = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// A name chosen by the account to recognize the token
+    pub name: MaxStr<255>,
+
+    /// Hex encoded SHA-256 hash of the token
+    #[rorm(unique)]
+    pub token_hash: MaxStr<64>,
+
+    /// What the token is allowed to do
+    pub scope: ApiTokenScope,
+
+    /// The point in time the token was created
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+
+    /// The point in time after which the token is no longer valid
+    pub expires_at: Option<OffsetDateTime>,
+
+    /// The point in time the token was last used
+    pub last_used_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "ApiTokenModel")]
+pub struct ApiTokenModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub name: MaxStr<255>,
+    pub token_hash: MaxStr<64>,
+    pub scope: ApiTokenScope,
+    pub expires_at: Option<OffsetDateTime>,
+    pub last_used_at: Option<OffsetDateTime>,
+}
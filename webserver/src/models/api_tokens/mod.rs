@@ -0,0 +1,206 @@
+//! API token model
+
+use std::ops::Deref;
+
+use galvyn::core::re_exports::rorm;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm::DbEnum;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::api_tokens::db::ApiTokenModel;
+use crate::models::api_tokens::db::ApiTokenModelInsert;
+
+pub(in crate::models) mod db;
+
+/// Prefix of every token to make them recognizable (e.g. for secret scanners)
+const TOKEN_PREFIX: &str = "tg_";
+
+/// Number of random characters in a token
+const TOKEN_LENGTH: usize = 48;
+
+/// What an API token is allowed to do
+#[derive(DbEnum, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ApiTokenScope {
+    /// Only requests which don't modify anything (`GET` and `HEAD`)
+    ReadOnly,
+    /// All requests
+    ReadWrite,
+}
+
+/// A token authenticating an account against the API
+///
+/// The token itself is only known during creation, afterward only its hash is stored.
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The account the token authenticates as
+    pub account: Uuid,
+
+    /// A name chosen by the account to recognize the token
+    pub name: MaxStr<255>,
+
+    /// What the token is allowed to do
+    pub scope: ApiTokenScope,
+
+    /// The point in time the token was created
+    pub created_at: OffsetDateTime,
+
+    /// The point in time after which the token is no longer valid
+    pub expires_at: Option<OffsetDateTime>,
+
+    /// The point in time the token was last used
+    pub last_used_at: Option<OffsetDateTime>,
+}
+
+impl ApiToken {
+    /// Create a new token for an account
+    ///
+    /// # Returns
+    /// The token's metadata and the token itself which can't be retrieved later on
+    #[instrument(name = "ApiToken::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        account: Uuid,
+        name: MaxStr<255>,
+        scope: ApiTokenScope,
+        expires_at: Option<OffsetDateTime>,
+    ) -> anyhow::Result<(ApiToken, String)> {
+        let token = generate_token(TOKEN_PREFIX);
+
+        let model = rorm::insert(exe, ApiTokenModel)
+            .single(&ApiTokenModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account),
+                name,
+                token_hash: hash_token(&token),
+                scope,
+                expires_at,
+                last_used_at: None,
+            })
+            .await?;
+
+        Ok((ApiToken::from(model), token))
+    }
+
+    /// Retrieve all tokens of an account
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        account: Uuid,
+    ) -> anyhow::Result<Vec<ApiToken>> {
+        let tokens = rorm::query(exe, ApiTokenModel)
+            .condition(ApiTokenModel.account.equals(account))
+            .all()
+            .await?;
+        Ok(tokens.into_iter().map(ApiToken::from).collect())
+    }
+
+    /// Find the token matching a secret presented by a client
+    ///
+    /// Expired tokens are not returned.
+    /// The token's `last_used_at` is updated on success.
+    #[instrument(name = "ApiToken::authenticate", skip_all)]
+    pub async fn authenticate(
+        exe: impl Executor<'_>,
+        token: &str,
+    ) -> anyhow::Result<Option<ApiToken>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let token_hash = hash_token(token);
+        let Some(model) = rorm::query(guard.get_transaction(), ApiTokenModel)
+            .condition(ApiTokenModel.token_hash.equals(token_hash.deref()))
+            .optional()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let now = OffsetDateTime::now_utc();
+        if model.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Ok(None);
+        }
+
+        rorm::update(guard.get_transaction(), ApiTokenModel)
+            .set(ApiTokenModel.last_used_at, Some(now))
+            .condition(ApiTokenModel.uuid.equals(model.uuid))
+            .await?;
+
+        guard.commit().await?;
+
+        let mut token = ApiToken::from(model);
+        token.last_used_at = Some(now);
+        Ok(Some(token))
+    }
+
+    /// Delete a token of an account
+    ///
+    /// # Returns
+    /// `false` if the account has no token with this uuid
+    #[instrument(name = "ApiToken::delete", skip(exe))]
+    pub async fn delete(exe: impl Executor<'_>, account: Uuid, uuid: Uuid) -> anyhow::Result<bool> {
+        let deleted = rorm::delete(exe, ApiTokenModel)
+            .condition(and![
+                ApiTokenModel.uuid.equals(uuid),
+                ApiTokenModel.account.equals(account)
+            ])
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    /// Check whether the token's scope permits a request with this method
+    pub fn permits(&self, method: &str) -> bool {
+        match self.scope {
+            ApiTokenScope::ReadOnly => matches!(method, "GET" | "HEAD"),
+            ApiTokenScope::ReadWrite => true,
+        }
+    }
+}
+
+/// Generate a new random token starting with `prefix`
+pub(in crate::models) fn generate_token(prefix: &str) -> String {
+    format!(
+        "{prefix}{}",
+        rand::thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect::<String>()
+    )
+}
+
+/// Hash a token for storing it in the database
+#[allow(
+    clippy::expect_used,
+    reason = "A hex encoded SHA-256 hash is always 64 characters"
+)]
+pub(in crate::models) fn hash_token(token: &str) -> MaxStr<64> {
+    MaxStr::new(format!("{:x}", Sha256::digest(token.as_bytes()))).expect("64 characters")
+}
+
+impl From<ApiTokenModel> for ApiToken {
+    fn from(value: ApiTokenModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            account: value.account.0,
+            name: value.name,
+            scope: value.scope,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            last_used_at: value.last_used_at,
+        }
+    }
+}
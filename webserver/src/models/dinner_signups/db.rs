@@ -0,0 +1,54 @@
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+use crate::models::dinners::db::DinnerModel;
+
+/// An account's sign-up for a dinner
+///
+/// The `DinnerSignup_dinner_account_key` index created in a raw migration
+/// ensures an account signs up at most once per dinner.
+#[derive(Debug, Model)]
+#[rorm(rename = "DinnerSignup")]
+pub struct DinnerSignupModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The dinner signed up for
+    #[rorm(on_delete = "Cascade", on_update = "Cascade")]
+    pub dinner: ForeignModel<DinnerModel>,
+
+    /// The account which signed up
+    #[rorm(on_delete = "Cascade", on_update = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// Number of portions including the ones for the account's guests
+    pub portions: i32,
+
+    /// The point in time the account signed up
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+
+    /// The point in time the account cancelled after the sign-up deadline
+    ///
+    /// Late cancellations are kept, so they can still be charged.
+    pub cancelled_at: Option<OffsetDateTime>,
+
+    /// Whether the sign-up waits for a free seat
+    #[rorm(default = false)]
+    pub waitlisted: bool,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "DinnerSignupModel")]
+pub struct DinnerSignupModelInsert {
+    pub uuid: Uuid,
+    pub dinner: ForeignModel<DinnerModel>,
+    pub account: ForeignModel<AccountModel>,
+    pub portions: i32,
+    pub cancelled_at: Option<OffsetDateTime>,
+    pub waitlisted: bool,
+}
@@ -0,0 +1,277 @@
+//! Dinner sign-up model
+
+use galvyn::core::re_exports::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::conditions::DynamicCollection;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::prelude::ForeignModelByField;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::dinner_signups::db::DinnerSignupModel;
+use crate::models::dinner_signups::db::DinnerSignupModelInsert;
+use crate::models::dinners::Dinner;
+
+pub(in crate::models) mod db;
+
+/// Maximum number of portions a single sign-up may order
+pub const MAX_PORTIONS: i32 = 10;
+
+/// An account's sign-up for a dinner (i.e. its "+1")
+///
+/// Every account signs up at most once per dinner.
+/// Portions for the account's guests are part of its sign-up.
+/// If a dinner is full, sign-ups are put on a waitlist ordered by the time they were made.
+#[derive(Debug, Clone)]
+pub struct DinnerSignup {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The dinner signed up for
+    pub dinner: Uuid,
+
+    /// The account which signed up
+    pub account: Uuid,
+
+    /// Number of portions including the ones for the account's guests
+    pub portions: i32,
+
+    /// The point in time the account signed up
+    pub created_at: OffsetDateTime,
+
+    /// The point in time the account cancelled after the sign-up deadline
+    ///
+    /// Late cancellations are kept, so they can still be charged.
+    pub cancelled_at: Option<OffsetDateTime>,
+
+    /// Whether the sign-up waits for a free seat
+    pub waitlisted: bool,
+}
+
+impl DinnerSignup {
+    /// Sign an account up for a dinner
+    ///
+    /// The dinner is [locked](Dinner::lock), so concurrent requests can't sign an account up twice.
+    /// Returns `None` if the account has already signed up.
+    #[instrument(name = "DinnerSignup::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        dinner: Uuid,
+        account: Uuid,
+        portions: i32,
+        waitlisted: bool,
+    ) -> anyhow::Result<Option<DinnerSignup>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        Dinner::lock(guard.get_transaction(), dinner).await?;
+        if DinnerSignup::find_by_account(guard.get_transaction(), dinner, account)
+            .await?
+            .is_some()
+        {
+            return Ok(None);
+        }
+
+        let model = rorm::insert(guard.get_transaction(), DinnerSignupModel)
+            .single(&DinnerSignupModelInsert {
+                uuid: Uuid::new_v4(),
+                dinner: ForeignModelByField(dinner),
+                account: ForeignModelByField(account),
+                portions,
+                cancelled_at: None,
+                waitlisted,
+            })
+            .await?;
+
+        guard.commit().await?;
+        Ok(Some(DinnerSignup::from(model)))
+    }
+
+    /// Find the sign-up of an account for a dinner
+    pub async fn find_by_account(
+        exe: impl Executor<'_>,
+        dinner: Uuid,
+        account: Uuid,
+    ) -> anyhow::Result<Option<DinnerSignup>> {
+        let signup = rorm::query(exe, DinnerSignupModel)
+            .condition(and![
+                DinnerSignupModel.dinner.equals(dinner),
+                DinnerSignupModel.account.equals(account)
+            ])
+            .optional()
+            .await?;
+        Ok(signup.map(DinnerSignup::from))
+    }
+
+    /// Retrieve the sign-ups for a dinner in the order they were made
+    pub async fn find_all_by_dinner(
+        exe: impl Executor<'_>,
+        dinner: Uuid,
+    ) -> anyhow::Result<Vec<DinnerSignup>> {
+        let mut signups: Vec<_> = rorm::query(exe, DinnerSignupModel)
+            .condition(DinnerSignupModel.dinner.equals(dinner))
+            .all()
+            .await?
+            .into_iter()
+            .map(DinnerSignup::from)
+            .collect();
+        signups.sort_by_key(|signup| signup.created_at);
+        Ok(signups)
+    }
+
+    /// Retrieve the sign-ups for several dinners in the order they were made
+    pub async fn find_all_by_dinners(
+        exe: impl Executor<'_>,
+        dinners: &[Uuid],
+    ) -> anyhow::Result<Vec<DinnerSignup>> {
+        if dinners.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut signups: Vec<_> = rorm::query(exe, DinnerSignupModel)
+            .condition(DynamicCollection::or(
+                dinners
+                    .iter()
+                    .map(|dinner| DinnerSignupModel.dinner.equals(*dinner))
+                    .collect(),
+            ))
+            .all()
+            .await?
+            .into_iter()
+            .map(DinnerSignup::from)
+            .collect();
+        signups.sort_by_key(|signup| signup.created_at);
+        Ok(signups)
+    }
+
+    /// Change the number of portions of the current sign-up
+    #[instrument(name = "DinnerSignup::set_portions", skip(self, exe))]
+    pub async fn set_portions(
+        &mut self,
+        exe: impl Executor<'_>,
+        portions: i32,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, DinnerSignupModel)
+            .set(DinnerSignupModel.portions, portions)
+            .condition(DinnerSignupModel.uuid.equals(self.uuid))
+            .await?;
+        self.portions = portions;
+        Ok(())
+    }
+
+    /// Mark the current sign-up as cancelled after the sign-up deadline
+    #[instrument(name = "DinnerSignup::cancel", skip(self, exe))]
+    pub async fn cancel(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let now = OffsetDateTime::now_utc();
+        rorm::update(exe, DinnerSignupModel)
+            .set(DinnerSignupModel.cancelled_at, Some(now))
+            .condition(DinnerSignupModel.uuid.equals(self.uuid))
+            .await?;
+        self.cancelled_at = Some(now);
+        Ok(())
+    }
+
+    /// Revert a late cancellation of the current sign-up
+    #[instrument(name = "DinnerSignup::restore", skip(self, exe))]
+    pub async fn restore(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::update(exe, DinnerSignupModel)
+            .set(DinnerSignupModel.cancelled_at, None)
+            .condition(DinnerSignupModel.uuid.equals(self.uuid))
+            .await?;
+        self.cancelled_at = None;
+        Ok(())
+    }
+
+    /// Take the current sign-up off the waitlist or put it back on
+    #[instrument(name = "DinnerSignup::set_waitlisted", skip(self, exe))]
+    pub async fn set_waitlisted(
+        &mut self,
+        exe: impl Executor<'_>,
+        waitlisted: bool,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, DinnerSignupModel)
+            .set(DinnerSignupModel.waitlisted, waitlisted)
+            .condition(DinnerSignupModel.uuid.equals(self.uuid))
+            .await?;
+        self.waitlisted = waitlisted;
+        Ok(())
+    }
+
+    /// Take sign-ups off a dinner's waitlist as long as there are enough free seats
+    ///
+    /// The waitlist is processed in order and stops at the first sign-up which doesn't fit,
+    /// so smaller sign-ups can't overtake it.
+    /// Without a capacity, the whole waitlist is promoted.
+    /// The dinner is [locked](Dinner::lock) while counting its seats.
+    ///
+    /// # Returns
+    /// The promoted sign-ups
+    #[instrument(name = "DinnerSignup::promote_waitlist", skip(exe))]
+    pub async fn promote_waitlist(
+        exe: impl Executor<'_>,
+        dinner: Uuid,
+        capacity: Option<i32>,
+    ) -> anyhow::Result<Vec<DinnerSignup>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        Dinner::lock(guard.get_transaction(), dinner).await?;
+        let signups = DinnerSignup::find_all_by_dinner(guard.get_transaction(), dinner).await?;
+        let mut free_seats =
+            capacity.map(|capacity| capacity - DinnerSignup::seats_taken(&signups));
+
+        let mut promoted = Vec::new();
+        for mut signup in signups.into_iter().filter(|signup| signup.waitlisted) {
+            if let Some(free_seats) = free_seats.as_mut() {
+                if signup.portions > *free_seats {
+                    break;
+                }
+                *free_seats -= signup.portions;
+            }
+            signup
+                .set_waitlisted(guard.get_transaction(), false)
+                .await?;
+            promoted.push(signup);
+        }
+
+        guard.commit().await?;
+        Ok(promoted)
+    }
+
+    /// Number of seats taken by sign-ups
+    ///
+    /// Waitlisted sign-ups and late cancellations don't take any seats.
+    pub fn seats_taken(signups: &[DinnerSignup]) -> i32 {
+        signups
+            .iter()
+            .filter(|signup| !signup.waitlisted && !signup.is_cancelled())
+            .map(|signup| signup.portions)
+            .sum()
+    }
+
+    /// Check whether the current sign-up has been cancelled late
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled_at.is_some()
+    }
+
+    /// Withdraw the current sign-up
+    #[instrument(name = "DinnerSignup::delete", skip(self, exe))]
+    pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, DinnerSignupModel)
+            .condition(DinnerSignupModel.uuid.equals(self.uuid))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<DinnerSignupModel> for DinnerSignup {
+    fn from(value: DinnerSignupModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            dinner: value.dinner.0,
+            account: value.account.0,
+            portions: value.portions,
+            created_at: value.created_at,
+            cancelled_at: value.cancelled_at,
+            waitlisted: value.waitlisted,
+        }
+    }
+}
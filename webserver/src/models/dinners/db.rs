@@ -0,0 +1,64 @@
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::Date;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+use crate::models::dinners::DinnerState;
+
+/// A dinner cooked for the community
+#[derive(Debug, Model)]
+#[rorm(rename = "Dinner")]
+pub struct DinnerModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The day the dinner takes place
+    pub date: Date,
+
+    /// What is going to be cooked
+    pub title: MaxStr<255>,
+
+    /// Further details like ingredients or allergens
+    pub description: MaxStr<4096>,
+
+    /// The account cooking the dinner
+    ///
+    /// Accounts are deactivated instead of deleted, so deleting a cook is refused
+    /// rather than silently dropping their dinners and the sign-ups for them.
+    #[rorm(on_delete = "Restrict", on_update = "Cascade")]
+    pub cook: ForeignModel<AccountModel>,
+
+    /// The state of the dinner
+    pub state: DinnerState,
+
+    /// The point in time sign-ups close
+    ///
+    /// If it is not set, the configured default time on the dinner's day is used.
+    pub signup_deadline: Option<OffsetDateTime>,
+
+    /// Maximum number of portions (i.e. seats) for the dinner
+    ///
+    /// Sign-ups beyond it are put on the waitlist.
+    pub capacity: Option<i32>,
+
+    /// The point in time the dinner was created
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "DinnerModel")]
+pub struct DinnerModelInsert {
+    pub uuid: Uuid,
+    pub date: Date,
+    pub title: MaxStr<255>,
+    pub description: MaxStr<4096>,
+    pub cook: ForeignModel<AccountModel>,
+    pub state: DinnerState,
+    pub signup_deadline: Option<OffsetDateTime>,
+    pub capacity: Option<i32>,
+}
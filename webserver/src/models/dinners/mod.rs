@@ -0,0 +1,257 @@
+//! Dinner model
+
+use galvyn::core::re_exports::rorm;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm::DbEnum;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::db::executor::Nothing;
+use galvyn::rorm::db::sql::value::Value;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use serde::Deserialize;
+use serde::Serialize;
+use time::Date;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::config::DEFAULT_SIGNUP_DEADLINE;
+use crate::config::TIMEZONE;
+use crate::models::dinners::db::DinnerModel;
+use crate::models::dinners::db::DinnerModelInsert;
+
+pub(in crate::models) mod db;
+
+/// The state of a dinner
+#[derive(DbEnum, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum DinnerState {
+    /// The dinner is going to take place
+    Planned,
+    /// The dinner has taken place
+    Served,
+    /// The dinner has been called off
+    Cancelled,
+}
+
+/// A dinner cooked for the community
+#[derive(Debug, Clone)]
+pub struct Dinner {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The day the dinner takes place
+    pub date: Date,
+
+    /// What is going to be cooked
+    pub title: MaxStr<255>,
+
+    /// Further details like ingredients or allergens
+    pub description: MaxStr<4096>,
+
+    /// The account cooking the dinner
+    pub cook: Uuid,
+
+    /// The state of the dinner
+    pub state: DinnerState,
+
+    /// The point in time sign-ups close
+    ///
+    /// If it is not set, the configured default time on the dinner's day is used.
+    /// Use [`Dinner::signups_close_at`] to get the effective deadline.
+    pub signup_deadline: Option<OffsetDateTime>,
+
+    /// Maximum number of portions (i.e. seats) for the dinner
+    ///
+    /// Sign-ups beyond it are put on the waitlist.
+    pub capacity: Option<i32>,
+
+    /// The point in time the dinner was created
+    pub created_at: OffsetDateTime,
+}
+
+/// The details of a dinner which can be changed after its creation
+#[derive(Debug, Clone)]
+pub struct DinnerUpdate {
+    /// The day the dinner takes place
+    pub date: Date,
+
+    /// What is going to be cooked
+    pub title: MaxStr<255>,
+
+    /// Further details like ingredients or allergens
+    pub description: MaxStr<4096>,
+
+    /// The account cooking the dinner
+    pub cook: Uuid,
+
+    /// The state of the dinner
+    pub state: DinnerState,
+
+    /// The point in time sign-ups close
+    pub signup_deadline: Option<OffsetDateTime>,
+
+    /// Maximum number of portions (i.e. seats) for the dinner
+    pub capacity: Option<i32>,
+}
+
+impl Dinner {
+    /// Create a new planned dinner
+    #[instrument(name = "Dinner::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        date: Date,
+        title: MaxStr<255>,
+        description: MaxStr<4096>,
+        cook: Uuid,
+        signup_deadline: Option<OffsetDateTime>,
+        capacity: Option<i32>,
+    ) -> anyhow::Result<Dinner> {
+        let model = rorm::insert(exe, DinnerModel)
+            .single(&DinnerModelInsert {
+                uuid: Uuid::new_v4(),
+                date,
+                title,
+                description,
+                cook: ForeignModelByField(cook),
+                state: DinnerState::Planned,
+                signup_deadline,
+                capacity,
+            })
+            .await?;
+        Ok(Dinner::from(model))
+    }
+
+    /// Find a dinner by its primary key
+    pub async fn find_by_uuid(
+        exe: impl Executor<'_>,
+        uuid: Uuid,
+    ) -> anyhow::Result<Option<Dinner>> {
+        let dinner = rorm::query(exe, DinnerModel)
+            .condition(DinnerModel.uuid.equals(uuid))
+            .optional()
+            .await?;
+        Ok(dinner.map(Dinner::from))
+    }
+
+    /// Lock the dinner's row until the end of the transaction
+    ///
+    /// Concurrent transactions locking the same dinner wait for this one to finish.
+    /// This serializes changes to a dinner's sign-ups.
+    pub async fn lock(exe: impl Executor<'_>, uuid: Uuid) -> anyhow::Result<()> {
+        exe.execute::<Nothing>(
+            r#"SELECT "uuid" FROM "Dinner" WHERE "uuid" = $1 FOR UPDATE;"#.to_string(),
+            vec![Value::Uuid(uuid)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieve a page of the dinners ordered by their date
+    pub async fn find_page(
+        exe: impl Executor<'_>,
+        limit: u64,
+        offset: u64,
+    ) -> anyhow::Result<Vec<Dinner>> {
+        let dinners = rorm::query(exe, DinnerModel)
+            .order_asc(DinnerModel.date)
+            .limit(limit)
+            .offset(offset)
+            .all()
+            .await?;
+        Ok(dinners.into_iter().map(Dinner::from).collect())
+    }
+
+    /// Retrieve the dinners taking place between two days (inclusive) ordered by their date
+    pub async fn find_by_date_range(
+        exe: impl Executor<'_>,
+        from: Date,
+        to: Date,
+    ) -> anyhow::Result<Vec<Dinner>> {
+        let dinners = rorm::query(exe, DinnerModel)
+            .condition(and![
+                DinnerModel.date.greater_equals(from),
+                DinnerModel.date.less_equals(to)
+            ])
+            .order_asc(DinnerModel.date)
+            .all()
+            .await?;
+        Ok(dinners.into_iter().map(Dinner::from).collect())
+    }
+
+    /// Update the details of the current dinner
+    #[instrument(name = "Dinner::update", skip(self, exe))]
+    pub async fn update(
+        &mut self,
+        exe: impl Executor<'_>,
+        update: DinnerUpdate,
+    ) -> anyhow::Result<()> {
+        let DinnerUpdate {
+            date,
+            title,
+            description,
+            cook,
+            state,
+            signup_deadline,
+            capacity,
+        } = update;
+        rorm::update(exe, DinnerModel)
+            .set(DinnerModel.date, date)
+            .set(DinnerModel.title, title.clone())
+            .set(DinnerModel.description, description.clone())
+            .set(DinnerModel.cook, ForeignModelByField(cook))
+            .set(DinnerModel.state, state)
+            .set(DinnerModel.signup_deadline, signup_deadline)
+            .set(DinnerModel.capacity, capacity)
+            .condition(DinnerModel.uuid.equals(self.uuid))
+            .await?;
+        self.date = date;
+        self.title = title;
+        self.description = description;
+        self.cook = cook;
+        self.state = state;
+        self.signup_deadline = signup_deadline;
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// The point in time sign-ups for the current dinner close
+    pub fn signups_close_at(&self) -> OffsetDateTime {
+        self.signup_deadline.unwrap_or_else(|| {
+            TIMEZONE
+                .get()
+                .at(self.date, DEFAULT_SIGNUP_DEADLINE.get().0)
+        })
+    }
+
+    /// Check whether sign-ups for the current dinner are closed
+    pub fn signups_closed(&self) -> bool {
+        OffsetDateTime::now_utc() >= self.signups_close_at()
+    }
+
+    /// Delete the current dinner
+    #[instrument(name = "Dinner::delete", skip(self, exe))]
+    pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, DinnerModel)
+            .condition(DinnerModel.uuid.equals(self.uuid))
+            .await?;
+        Ok(())
+    }
+}
+
+impl From<DinnerModel> for Dinner {
+    fn from(value: DinnerModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            date: value.date,
+            title: value.title,
+            description: value.description,
+            cook: value.cook.0,
+            state: value.state,
+            signup_deadline: value.signup_deadline,
+            capacity: value.capacity,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+
+/// A one-time token logging a guest account in
+#[derive(Debug, Model)]
+#[rorm(rename = "GuestLoginToken")]
+pub struct GuestLoginTokenModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The guest account the token logs in as
+    #[rorm(on_delete = "Cascade", on_update = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// Hex encoded SHA-256 hash of the token
+    #[rorm(unique)]
+    pub token_hash: MaxStr<64>,
+
+    /// The point in time the token was created
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+
+    /// The point in time after which the token is no longer valid
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "GuestLoginTokenModel")]
+pub struct GuestLoginTokenModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub token_hash: MaxStr<64>,
+    pub expires_at: OffsetDateTime,
+}
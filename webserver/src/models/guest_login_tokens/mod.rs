@@ -0,0 +1,111 @@
+//! Guest login token model
+
+use std::ops::Deref;
+
+use galvyn::core::re_exports::rorm;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::prelude::ForeignModelByField;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::api_tokens::generate_token;
+use crate::models::api_tokens::hash_token;
+use crate::models::guest_login_tokens::db::GuestLoginTokenModel;
+use crate::models::guest_login_tokens::db::GuestLoginTokenModelInsert;
+
+pub(in crate::models) mod db;
+
+/// Prefix of every token to make them recognizable (e.g. for secret scanners)
+const TOKEN_PREFIX: &str = "tgl_";
+
+/// Time a login link stays valid
+const TOKEN_LIFETIME: Duration = Duration::days(7);
+
+/// A one-time token logging a guest account in
+///
+/// Guests don't have an oidc account, so their sponsor hands them a link containing the token.
+/// The token itself is only known during creation, afterward only its hash is stored.
+#[derive(Debug, Clone)]
+pub struct GuestLoginToken {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The guest account the token logs in as
+    pub account: Uuid,
+
+    /// The point in time the token was created
+    pub created_at: OffsetDateTime,
+
+    /// The point in time after which the token is no longer valid
+    pub expires_at: OffsetDateTime,
+}
+
+impl GuestLoginToken {
+    /// Create a new token for a guest account
+    ///
+    /// # Returns
+    /// The token's metadata and the token itself which can't be retrieved later on
+    #[instrument(name = "GuestLoginToken::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        account: Uuid,
+    ) -> anyhow::Result<(GuestLoginToken, String)> {
+        let token = generate_token(TOKEN_PREFIX);
+
+        let model = rorm::insert(exe, GuestLoginTokenModel)
+            .single(&GuestLoginTokenModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account),
+                token_hash: hash_token(&token),
+                expires_at: OffsetDateTime::now_utc() + TOKEN_LIFETIME,
+            })
+            .await?;
+
+        Ok((GuestLoginToken::from(model), token))
+    }
+
+    /// Consume the token matching a secret presented by a client
+    ///
+    /// The token is deleted, so it can't be used a second time.
+    /// Expired tokens are deleted as well but not returned.
+    #[instrument(name = "GuestLoginToken::redeem", skip_all)]
+    pub async fn redeem(
+        exe: impl Executor<'_>,
+        token: &str,
+    ) -> anyhow::Result<Option<GuestLoginToken>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let token_hash = hash_token(token);
+        let Some(model) = rorm::query(guard.get_transaction(), GuestLoginTokenModel)
+            .condition(GuestLoginTokenModel.token_hash.equals(token_hash.deref()))
+            .optional()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        rorm::delete(guard.get_transaction(), GuestLoginTokenModel)
+            .condition(GuestLoginTokenModel.uuid.equals(model.uuid))
+            .await?;
+
+        guard.commit().await?;
+
+        if model.expires_at <= OffsetDateTime::now_utc() {
+            return Ok(None);
+        }
+        Ok(Some(GuestLoginToken::from(model)))
+    }
+}
+
+impl From<GuestLoginTokenModel> for GuestLoginToken {
+    fn from(value: GuestLoginTokenModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            account: value.account.0,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+        }
+    }
+}
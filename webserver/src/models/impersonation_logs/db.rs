@@ -0,0 +1,48 @@
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+
+/// A request performed by an admin while impersonating another account
+#[derive(Debug, Model)]
+#[rorm(rename = "ImpersonationLog")]
+pub struct ImpersonationLogModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The admin performing the request
+    ///
+    /// This is `None` if the admin's account has been deleted since.
+    #[rorm(on_delete = "SetNull", on_update = "Cascade")]
+    pub admin: Option<ForeignModel<AccountModel>>,
+
+    /// The account the request was performed as
+    ///
+    /// This is `None` if the account has been deleted since.
+    #[rorm(on_delete = "SetNull", on_update = "Cascade")]
+    pub account: Option<ForeignModel<AccountModel>>,
+
+    /// The http method of the request
+    pub method: MaxStr<16>,
+
+    /// The path of the request
+    pub path: MaxStr<2048>,
+
+    /// The point in time the request was performed
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "ImpersonationLogModel")]
+pub struct ImpersonationLogModelInsert {
+    pub uuid: Uuid,
+    pub admin: Option<ForeignModel<AccountModel>>,
+    pub account: Option<ForeignModel<AccountModel>>,
+    pub method: MaxStr<16>,
+    pub path: MaxStr<2048>,
+}
@@ -0,0 +1,107 @@
+//! Impersonation log model
+
+use galvyn::core::re_exports::rorm;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::or;
+use galvyn::rorm::prelude::ForeignModelByField;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::impersonation_logs::db::ImpersonationLogModel;
+use crate::models::impersonation_logs::db::ImpersonationLogModelInsert;
+use crate::utils::max_str::truncate;
+
+pub(in crate::models) mod db;
+
+/// The [`ImpersonationLog::method`] of the entry recording the start of an impersonation
+pub const IMPERSONATION_STARTED: &str = "IMPERSONATE";
+
+/// A request performed by an admin while impersonating another account
+///
+/// Every request of an impersonating session is recorded,
+/// so it can be traced back what an admin did on behalf of an account.
+#[derive(Debug, Clone)]
+pub struct ImpersonationLog {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The admin performing the request
+    ///
+    /// This is `None` if the admin's account has been deleted since.
+    pub admin: Option<Uuid>,
+
+    /// The account the request was performed as
+    ///
+    /// This is `None` if the account has been deleted since.
+    pub account: Option<Uuid>,
+
+    /// The http method of the request
+    ///
+    /// This is [`IMPERSONATION_STARTED`] for the entry recording the start of the impersonation.
+    pub method: MaxStr<16>,
+
+    /// The path of the request
+    pub path: MaxStr<2048>,
+
+    /// The point in time the request was performed
+    pub created_at: OffsetDateTime,
+}
+
+impl ImpersonationLog {
+    /// Record a request performed while impersonating an account
+    ///
+    /// Overlong paths are cut off.
+    pub async fn create(
+        exe: impl Executor<'_>,
+        admin: Uuid,
+        account: Uuid,
+        method: &str,
+        path: &str,
+    ) -> anyhow::Result<ImpersonationLog> {
+        let model = rorm::insert(exe, ImpersonationLogModel)
+            .single(&ImpersonationLogModelInsert {
+                uuid: Uuid::new_v4(),
+                admin: Some(ForeignModelByField(admin)),
+                account: Some(ForeignModelByField(account)),
+                method: truncate(method)?,
+                path: truncate(path)?,
+            })
+            .await?;
+        Ok(ImpersonationLog::from(model))
+    }
+
+    /// Retrieve the log entries an account is involved in, either as admin or as impersonated account
+    ///
+    /// The entries are ordered from newest to oldest.
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        account: Uuid,
+    ) -> anyhow::Result<Vec<ImpersonationLog>> {
+        let mut entries: Vec<_> = rorm::query(exe, ImpersonationLogModel)
+            .condition(or![
+                ImpersonationLogModel.admin.equals(account),
+                ImpersonationLogModel.account.equals(account)
+            ])
+            .all()
+            .await?
+            .into_iter()
+            .map(ImpersonationLog::from)
+            .collect();
+        entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(entries)
+    }
+}
+
+impl From<ImpersonationLogModel> for ImpersonationLog {
+    fn from(value: ImpersonationLogModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            admin: value.admin.map(|admin| admin.0),
+            account: value.account.map(|account| account.0),
+            method: value.method,
+            path: value.path,
+            created_at: value.created_at,
+        }
+    }
+}
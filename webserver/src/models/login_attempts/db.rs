@@ -0,0 +1,57 @@
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use galvyn::rorm::{Model, Patch};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::accounts::db::AccountModel;
+
+/// An attempt to log in
+#[derive(Debug, Model)]
+#[rorm(rename = "LoginAttempt")]
+pub struct LoginAttemptModel {
+    /// Primary key
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// The oidc provider used to log in or how a guest logged in
+    pub provider: MaxStr<255>,
+
+    /// The subject the oidc provider authenticated
+    ///
+    /// This is `None` if the login failed before the provider confirmed a subject.
+    pub subject: Option<MaxStr<255>>,
+
+    /// The account which has been logged in
+    #[rorm(on_delete = "SetNull", on_update = "Cascade")]
+    pub account: Option<ForeignModel<AccountModel>>,
+
+    /// Whether the login succeeded
+    pub success: bool,
+
+    /// Why the login failed
+    pub failure_reason: Option<MaxStr<255>>,
+
+    /// The `User-Agent` of the browser attempting to log in
+    pub user_agent: Option<MaxStr<1024>>,
+
+    /// The ip address the attempt originated from
+    pub ip: Option<MaxStr<64>>,
+
+    /// The point in time of the attempt
+    #[rorm(auto_create_time)]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "LoginAttemptModel")]
+pub struct LoginAttemptModelInsert {
+    pub uuid: Uuid,
+    pub provider: MaxStr<255>,
+    pub subject: Option<MaxStr<255>>,
+    pub account: Option<ForeignModel<AccountModel>>,
+    pub success: bool,
+    pub failure_reason: Option<MaxStr<255>>,
+    pub user_agent: Option<MaxStr<1024>>,
+    pub ip: Option<MaxStr<64>>,
+}
@@ -0,0 +1,153 @@
+//! Login attempt model
+
+use galvyn::core::re_exports::rorm;
+use galvyn::rorm::conditions::Condition;
+use galvyn::rorm::conditions::DynamicCollection;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::account_sessions::ClientInfo;
+use crate::models::login_attempts::db::LoginAttemptModel;
+use crate::models::login_attempts::db::LoginAttemptModelInsert;
+use crate::utils::max_str::truncate;
+
+pub(in crate::models) mod db;
+
+/// The [`LoginAttempt::provider`] of logins through a guest's login link
+pub const GUEST_LOGIN_LINK: &str = "guest-login-link";
+
+/// An attempt to log in
+///
+/// Attempts are deleted after [`LOGIN_LOG_RETENTION_DAYS`](crate::config::LOGIN_LOG_RETENTION_DAYS).
+#[derive(Debug, Clone)]
+pub struct LoginAttempt {
+    /// Primary key
+    pub uuid: Uuid,
+
+    /// The oidc provider used to log in or [`GUEST_LOGIN_LINK`]
+    pub provider: MaxStr<255>,
+
+    /// The subject the oidc provider authenticated
+    pub subject: Option<MaxStr<255>>,
+
+    /// The account which has been logged in
+    pub account: Option<Uuid>,
+
+    /// Whether the login succeeded
+    pub success: bool,
+
+    /// Why the login failed
+    pub failure_reason: Option<MaxStr<255>>,
+
+    /// The `User-Agent` of the browser attempting to log in
+    pub user_agent: Option<MaxStr<1024>>,
+
+    /// The ip address the attempt originated from
+    pub ip: Option<MaxStr<64>>,
+
+    /// The point in time of the attempt
+    pub created_at: OffsetDateTime,
+}
+
+impl LoginAttempt {
+    /// Record an attempt to log in
+    ///
+    /// The attempt succeeded if no `failure_reason` is given.
+    #[instrument(name = "LoginAttempt::create", skip(exe, client))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        provider: &str,
+        subject: Option<&str>,
+        account: Option<Uuid>,
+        failure_reason: Option<&str>,
+        client: ClientInfo,
+    ) -> anyhow::Result<LoginAttempt> {
+        let model = rorm::insert(exe, LoginAttemptModel)
+            .single(&LoginAttemptModelInsert {
+                uuid: Uuid::new_v4(),
+                provider: truncate(provider)?,
+                subject: subject.map(truncate).transpose()?,
+                account: account.map(ForeignModelByField),
+                success: failure_reason.is_none(),
+                failure_reason: failure_reason.map(truncate).transpose()?,
+                user_agent: client.user_agent,
+                ip: client.ip,
+            })
+            .await?;
+        Ok(LoginAttempt::from(model))
+    }
+
+    /// Retrieve a page of the recorded attempts
+    ///
+    /// They can be restricted to a single `account` and to failed attempts.
+    /// The attempts are ordered from newest to oldest.
+    pub async fn find_page(
+        exe: impl Executor<'_>,
+        account: Option<Uuid>,
+        failed_only: bool,
+        limit: u64,
+        offset: u64,
+    ) -> anyhow::Result<Vec<LoginAttempt>> {
+        let mut conditions = Vec::new();
+        if let Some(account) = account {
+            conditions.push(LoginAttemptModel.account.equals(account).boxed());
+        }
+        if failed_only {
+            conditions.push(LoginAttemptModel.success.equals(false).boxed());
+        }
+
+        let query = rorm::query(exe, LoginAttemptModel);
+        let models = if conditions.is_empty() {
+            query
+                .order_desc(LoginAttemptModel.created_at)
+                .limit(limit)
+                .offset(offset)
+                .all()
+                .await?
+        } else {
+            query
+                .condition(DynamicCollection::and(conditions))
+                .order_desc(LoginAttemptModel.created_at)
+                .limit(limit)
+                .offset(offset)
+                .all()
+                .await?
+        };
+        Ok(models.into_iter().map(LoginAttempt::from).collect())
+    }
+
+    /// Delete all attempts which happened before `cutoff`
+    ///
+    /// # Returns
+    /// The number of deleted attempts
+    #[instrument(name = "LoginAttempt::delete_older_than", skip(exe))]
+    pub async fn delete_older_than(
+        exe: impl Executor<'_>,
+        cutoff: OffsetDateTime,
+    ) -> anyhow::Result<u64> {
+        let deleted = rorm::delete(exe, LoginAttemptModel)
+            .condition(LoginAttemptModel.created_at.less_than(cutoff))
+            .await?;
+        Ok(deleted)
+    }
+}
+
+impl From<LoginAttemptModel> for LoginAttempt {
+    fn from(value: LoginAttemptModel) -> Self {
+        Self {
+            uuid: value.uuid,
+            provider: value.provider,
+            subject: value.subject,
+            account: value.account.map(|account| account.0),
+            success: value.success,
+            failure_reason: value.failure_reason,
+            user_agent: value.user_agent,
+            ip: value.ip,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -1,3 +1,10 @@
 //! All database models are defined in this module
 
+pub mod account_sessions;
 pub mod accounts;
+pub mod api_tokens;
+pub mod dinner_signups;
+pub mod dinners;
+pub mod guest_login_tokens;
+pub mod impersonation_logs;
+pub mod login_attempts;
@@ -0,0 +1,117 @@
+//! Galvyn [`Module`] distributing events to the websockets of logged-in accounts.
+
+use galvyn::core::InitError;
+use galvyn::core::Module;
+use galvyn::core::PreInitError;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Number of events a slow websocket may lag behind before it misses some
+const CAPACITY: usize = 1024;
+
+/// Galvyn [`Module`] distributing events to the websockets of logged-in accounts.
+pub struct EventBus {
+    /// Sender half of the channel every websocket subscribes to
+    sender: broadcast::Sender<Event>,
+}
+
+/// An event published on the [`EventBus`]
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// Who should receive the event
+    pub recipient: EventRecipient,
+
+    /// The message to send to the recipient's websockets
+    pub message: WsMessage,
+}
+
+/// The websockets an [`Event`] is delivered to
+#[derive(Debug, Copy, Clone)]
+pub enum EventRecipient {
+    /// Every logged-in account
+    All,
+    /// All sessions of an account
+    Account(Uuid),
+    /// A single [`AccountSession`](crate::models::account_sessions::AccountSession)
+    AccountSession(Uuid),
+}
+
+/// A message sent to the frontend through the websocket
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum WsMessage {
+    /// The session was logged out
+    ///
+    /// The websocket is closed after this message.
+    LoggedOut,
+    /// The account's balance changed
+    BalanceChanged {
+        /// The new balance
+        balance: i64,
+    },
+    /// The sign-ups for a dinner changed
+    DinnerSignupsChanged {
+        /// The dinner's primary key
+        dinner: Uuid,
+    },
+    /// The account's sign-up for a dinner was taken off the waitlist
+    PromotedFromWaitlist {
+        /// The dinner's primary key
+        dinner: Uuid,
+    },
+}
+
+impl EventBus {
+    /// Publishes an event
+    ///
+    /// Events are dropped silently if no websocket is connected.
+    pub fn publish(&self, recipient: EventRecipient, message: WsMessage) {
+        let _ = self.sender.send(Event { recipient, message });
+    }
+
+    /// Notifies the websockets of sessions which have been logged out
+    pub fn publish_logged_out(&self, account_sessions: impl IntoIterator<Item = Uuid>) {
+        for account_session in account_sessions {
+            self.publish(
+                EventRecipient::AccountSession(account_session),
+                WsMessage::LoggedOut,
+            );
+        }
+    }
+
+    /// Subscribes to all events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl EventRecipient {
+    /// Checks whether a websocket of an account's session should receive the event
+    pub fn matches(&self, account: Uuid, account_session: Option<Uuid>) -> bool {
+        match self {
+            EventRecipient::All => true,
+            EventRecipient::Account(uuid) => *uuid == account,
+            EventRecipient::AccountSession(uuid) => Some(*uuid) == account_session,
+        }
+    }
+}
+
+impl Module for EventBus {
+    type Setup = ();
+    type PreInit = Self;
+
+    async fn pre_init((): Self::Setup) -> Result<Self::PreInit, PreInitError> {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Ok(Self { sender })
+    }
+
+    type Dependencies = ();
+
+    async fn init(pre_init: Self::PreInit, (): &mut Self::Dependencies) -> Result<Self, InitError> {
+        Ok(pre_init)
+    }
+}
@@ -0,0 +1,329 @@
+//! Galvyn [`Module`] connecting accounts with an LDAP directory.
+
+use std::ops::Deref;
+use std::time::Duration;
+
+use galvyn::core::InitError;
+use galvyn::core::Module;
+use galvyn::core::PreInitError;
+use galvyn::rorm::Database;
+use galvyn::rorm::fields::types::MaxStr;
+use ldap3::LdapConnAsync;
+use ldap3::LdapConnSettings;
+use ldap3::LdapError;
+use ldap3::Scope;
+use ldap3::SearchEntry;
+use ldap3::ldap_escape;
+use tracing::error;
+use tracing::info;
+use tracing::instrument;
+use tracing::warn;
+
+use crate::config::LDAP_BASE_DN;
+use crate::config::LDAP_BIND_DN;
+use crate::config::LDAP_BIND_PASSWORD;
+use crate::config::LDAP_DISPLAY_NAME_ATTRIBUTE;
+use crate::config::LDAP_SYNC_INTERVAL;
+use crate::config::LDAP_URL;
+use crate::config::LDAP_USER_FILTER;
+use crate::models::accounts::Account;
+
+/// LDAP result code returned when the base object of a search does not exist
+const NO_SUCH_OBJECT: u32 = 32;
+
+/// Galvyn [`Module`] connecting accounts with an LDAP directory.
+pub struct Ldap {
+    /// Config set by admin to connect to the LDAP server.
+    config: LdapConfig,
+}
+
+/// A user found in the directory
+#[derive(Debug, Clone)]
+pub struct LdapUser {
+    /// The user's DN (distinguished name)
+    pub dn: MaxStr<2048>,
+
+    /// The user's display name, if the directory provides one
+    pub display_name: Option<MaxStr<255>>,
+}
+
+impl Ldap {
+    /// Resolves a username to the user in the directory
+    ///
+    /// # Returns
+    /// `None` if the directory does not contain exactly one user matching [`LDAP_USER_FILTER`]
+    #[instrument(name = "Ldap::find_user", skip(self))]
+    pub async fn find_user(&self, username: &str) -> Result<Option<LdapUser>, LdapError> {
+        let mut ldap = self.connect().await?;
+
+        let filter = self
+            .config
+            .user_filter
+            .replace("{username}", &ldap_escape(username));
+        let (entries, _) = ldap
+            .search(
+                &self.config.base_dn,
+                Scope::Subtree,
+                &filter,
+                [self.config.display_name_attribute.as_str()],
+            )
+            .await?
+            .success()?;
+        ldap.unbind().await?;
+
+        let mut entries = entries.into_iter();
+        let (Some(entry), None) = (entries.next(), entries.next()) else {
+            warn!("Username did not match exactly one entry in the directory");
+            return Ok(None);
+        };
+        Ok(self.parse_entry(SearchEntry::construct(entry)))
+    }
+
+    /// Spawns the task periodically synchronising all accounts with the directory
+    pub fn start_sync_task(&'static self) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.sync_interval);
+            loop {
+                interval.tick().await;
+                if let Err(error) = self.sync().await {
+                    error!(error.display = %error, error.debug = ?error, "LDAP synchronisation failed");
+                }
+            }
+        });
+    }
+
+    /// Synchronises all accounts linked to a DN with the directory
+    ///
+    /// Display names are refreshed and accounts whose DN disappeared are flagged.
+    /// A failing account is logged and skipped, so it doesn't block the others.
+    #[instrument(name = "Ldap::sync", skip(self))]
+    async fn sync(&self) -> anyhow::Result<()> {
+        let mut ldap = self.connect().await?;
+
+        for account in Account::find_all_in_directory(Database::global()).await? {
+            let uuid = account.uuid;
+            if let Err(error) = self.sync_account(&mut ldap, account).await {
+                warn!(
+                    account.uuid = %uuid,
+                    error.display = %error,
+                    error.debug = ?error,
+                    "Failed to synchronise account with the directory"
+                );
+            }
+        }
+
+        ldap.unbind().await?;
+        Ok(())
+    }
+
+    /// Synchronises a single account linked to a DN with the directory
+    async fn sync_account(
+        &self,
+        ldap: &mut ldap3::Ldap,
+        mut account: Account,
+    ) -> anyhow::Result<()> {
+        let db = Database::global();
+        let Some(ldap_dn) = account.ldap_dn.clone() else {
+            return Ok(());
+        };
+
+        let result = ldap
+            .search(
+                &ldap_dn,
+                Scope::Base,
+                "(objectClass=*)",
+                [self.config.display_name_attribute.as_str()],
+            )
+            .await?;
+
+        if result.1.rc == NO_SUCH_OBJECT {
+            if !account.ldap_dn_missing {
+                warn!(account.uuid = %account.uuid, "DN disappeared from the directory");
+                account.set_ldap_dn_missing(db, true).await?;
+            }
+            return Ok(());
+        }
+
+        let (entries, _) = result.success()?;
+        let Some(user) = entries
+            .into_iter()
+            .next()
+            .and_then(|entry| self.parse_entry(SearchEntry::construct(entry)))
+        else {
+            return Ok(());
+        };
+
+        if account.ldap_dn_missing {
+            info!(account.uuid = %account.uuid, "DN reappeared in the directory");
+            account.set_ldap_dn_missing(db, false).await?;
+        }
+        if let Some(display_name) = user
+            .display_name
+            .filter(|name| name.deref() != account.display_name.deref())
+        {
+            account.set_display_name(db, display_name).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Opens a new connection to the LDAP server and binds to it
+    async fn connect(&self) -> Result<ldap3::Ldap, LdapError> {
+        let (connection, mut ldap) = LdapConnAsync::with_settings(
+            LdapConnSettings::new().set_conn_timeout(Duration::from_secs(10)),
+            &self.config.url,
+        )
+        .await?;
+        ldap3::drive!(connection);
+
+        ldap.simple_bind(&self.config.bind_dn, &self.config.bind_password)
+            .await?
+            .success()?;
+
+        Ok(ldap)
+    }
+
+    /// Converts a search entry into a [`LdapUser`]
+    fn parse_entry(&self, entry: SearchEntry) -> Option<LdapUser> {
+        let dn = MaxStr::new(entry.dn)
+            .inspect_err(|_| warn!("DN is too long"))
+            .ok()?;
+        let display_name = entry
+            .attrs
+            .get(&self.config.display_name_attribute)
+            .and_then(|values| values.first())
+            .and_then(|value| MaxStr::new(value.clone()).ok());
+        Some(LdapUser { dn, display_name })
+    }
+}
+
+impl Module for Ldap {
+    type Setup = LdapConfig;
+    type PreInit = Self;
+
+    async fn pre_init(config: Self::Setup) -> Result<Self::PreInit, PreInitError> {
+        Ok(Self { config })
+    }
+
+    type Dependencies = ();
+
+    async fn init(pre_init: Self::PreInit, (): &mut Self::Dependencies) -> Result<Self, InitError> {
+        Ok(pre_init)
+    }
+}
+
+/// Config set by admin to connect to the LDAP server.
+pub struct LdapConfig {
+    /// The server's url
+    url: String,
+
+    /// The DN to bind with
+    bind_dn: String,
+
+    /// The password to bind with
+    bind_password: String,
+
+    /// The DN below which users are searched
+    base_dn: String,
+
+    /// The filter to find a user by its username
+    user_filter: String,
+
+    /// The attribute holding a user's display name
+    display_name_attribute: String,
+
+    /// The interval between two synchronisations
+    sync_interval: Duration,
+}
+
+impl LdapConfig {
+    /// Loads the config from environment variables
+    ///
+    /// # Returns
+    /// `None` if LDAP is disabled because [`LDAP_URL`] is unset
+    pub fn from_env() -> Option<Self> {
+        if LDAP_URL.is_empty() {
+            return None;
+        }
+        Some(LdapConfig {
+            url: LDAP_URL.clone(),
+            bind_dn: LDAP_BIND_DN.clone(),
+            bind_password: LDAP_BIND_PASSWORD.clone(),
+            base_dn: LDAP_BASE_DN.clone(),
+            user_filter: LDAP_USER_FILTER.clone(),
+            display_name_attribute: LDAP_DISPLAY_NAME_ATTRIBUTE.clone(),
+            sync_interval: Duration::from_secs(LDAP_SYNC_INTERVAL.get().get()),
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::time::Duration;
+
+    use ldap3::Scope;
+
+    use super::Ldap;
+    use super::LdapConfig;
+    use super::NO_SUCH_OBJECT;
+
+    /// Connects to the directory from `data/glauth/glauth-dev.cfg`
+    ///
+    /// The url can be overridden with `LDAP_TEST_URL`.
+    fn glauth_dev() -> Ldap {
+        Ldap {
+            config: LdapConfig {
+                url: std::env::var("LDAP_TEST_URL")
+                    .unwrap_or_else(|_| "ldap://glauth-dev:3893".to_string()),
+                bind_dn: "cn=search,ou=svc,ou=users,dc=tavern-goblin,dc=local".to_string(),
+                bind_password: "search".to_string(),
+                base_dn: "ou=users,dc=tavern-goblin,dc=local".to_string(),
+                user_filter: "(uid={username})".to_string(),
+                display_name_attribute: "givenName".to_string(),
+                sync_interval: Duration::from_secs(60),
+            },
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the glauth-dev container"]
+    async fn find_user_resolves_dn_and_display_name() {
+        let user = glauth_dev().find_user("alice").await.unwrap().unwrap();
+
+        assert_eq!(
+            &*user.dn,
+            "cn=alice,ou=members,ou=users,dc=tavern-goblin,dc=local"
+        );
+        assert_eq!(
+            user.display_name.as_deref().map(|name| &**name),
+            Some("Alice")
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the glauth-dev container"]
+    async fn find_user_returns_none_for_unknown_user() {
+        assert!(glauth_dev().find_user("mallory").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the glauth-dev container"]
+    async fn missing_dn_is_reported_as_no_such_object() {
+        let ldap = glauth_dev();
+        let mut connection = ldap.connect().await.unwrap();
+
+        let result = connection
+            .search(
+                "cn=mallory,ou=members,ou=users,dc=tavern-goblin,dc=local",
+                Scope::Base,
+                "(objectClass=*)",
+                ["givenName"],
+            )
+            .await
+            .unwrap();
+        connection.unbind().await.unwrap();
+
+        assert_eq!(result.1.rc, NO_SUCH_OBJECT);
+    }
+}
@@ -1 +1,3 @@
-pub mod oidc;
\ No newline at end of file
+pub mod event_bus;
+pub mod ldap;
+pub mod oidc;
@@ -1,63 +1,165 @@
 //! Galvyn [`Module`] containing the state and logic for OpenID Connect authentication.
 
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
 use std::time::Duration;
+use std::time::Instant;
 
 use galvyn::core::InitError;
 use galvyn::core::Module;
 use galvyn::core::PreInitError;
+use galvyn::core::re_exports::axum::http::HeaderValue;
+use galvyn::core::re_exports::axum::http::StatusCode;
+use galvyn::core::re_exports::axum::http::header;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
 use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
 use openidconnect::AccessTokenHash;
+use openidconnect::AdditionalClaims;
 use openidconnect::AuthorizationCode;
+use openidconnect::ClaimsVerificationError;
+use openidconnect::Client;
 use openidconnect::ClientId;
 use openidconnect::ClientSecret;
 use openidconnect::CsrfToken;
 use openidconnect::DiscoveryError;
+use openidconnect::EmptyExtraTokenFields;
+use openidconnect::EndSessionUrl;
 use openidconnect::EndpointMaybeSet;
 use openidconnect::EndpointNotSet;
 use openidconnect::EndpointSet;
 use openidconnect::HttpClientError;
+use openidconnect::IdToken;
+use openidconnect::IdTokenClaims;
+use openidconnect::IdTokenFields;
 use openidconnect::IssuerUrl;
+use openidconnect::LanguageTag;
+use openidconnect::LocalizedClaim;
+use openidconnect::LogoutRequest;
 use openidconnect::Nonce;
+use openidconnect::NonceVerifier;
 use openidconnect::OAuth2TokenResponse;
 use openidconnect::PkceCodeChallenge;
 use openidconnect::PkceCodeVerifier;
+use openidconnect::PostLogoutRedirectUrl;
+use openidconnect::ProviderMetadataWithLogout;
 use openidconnect::RedirectUrl;
 use openidconnect::RequestTokenError;
 use openidconnect::Scope;
+use openidconnect::SignatureVerificationError;
+use openidconnect::StandardErrorResponse;
+use openidconnect::StandardTokenResponse;
 use openidconnect::TokenResponse;
+use openidconnect::core::CoreAuthDisplay;
+use openidconnect::core::CoreAuthPrompt;
 use openidconnect::core::CoreAuthenticationFlow;
-use openidconnect::core::CoreClient;
-use openidconnect::core::CoreIdTokenClaims;
-use openidconnect::core::CoreProviderMetadata;
+use openidconnect::core::CoreErrorResponseType;
+use openidconnect::core::CoreGenderClaim;
+use openidconnect::core::CoreJsonWebKey;
+use openidconnect::core::CoreJweContentEncryptionAlgorithm;
+use openidconnect::core::CoreJwsSigningAlgorithm;
+use openidconnect::core::CoreRevocableToken;
+use openidconnect::core::CoreRevocationErrorResponse;
+use openidconnect::core::CoreTokenIntrospectionResponse;
+use openidconnect::core::CoreTokenType;
 use openidconnect::reqwest;
 use serde::Deserialize;
 use serde::Serialize;
+use tracing::debug;
 use tracing::error;
+use tracing::instrument;
 use tracing::warn;
 use url::Url;
 
-use crate::config::OIDC_CLIENT_ID;
-use crate::config::OIDC_CLIENT_SECRET;
-use crate::config::OIDC_DISCOVER_URL;
-use crate::config::OIDC_REDIRECT_URL;
+use crate::config::OIDC_PROVIDERS_FILE;
+use crate::config::OIDC_REFRESH_INTERVAL;
+use crate::models::accounts::AccountRole;
+
+/// Interval between two attempts to discover a provider which is unavailable
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Minimum time between two discoveries triggered by tokens signed with an unknown key
+const MIN_KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The event a logout token has to contain
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
 
 /// Galvyn [`Module`] containing the state and logic for OpenID Connect authentication.
 pub struct OpenIdConnect {
+    /// The configured providers in the order of the config file
+    providers: Vec<OidcProvider>,
+}
+
+/// A single oidc provider users can authenticate with
+pub struct OidcProvider {
+    /// Name identifying the provider in urls
+    pub name: String,
+
+    /// Whether users of this provider have to be part of the LDAP directory
+    pub ldap: bool,
+
     /// HTTP client used to send requests to the issuer.
     ///
     /// It is stored as a field to make efficient use of resources.
-    /// Most noteworthy, a connection pool.
+    /// Most noteworthy, a connection pool which is shared between all providers.
     http_client: reqwest::Client,
 
+    /// Config set by admin to connect to the provider
+    config: OidcProviderConfig,
+
+    /// The url the issuer should redirect users to after logging them out
+    post_logout_redirect_url: PostLogoutRedirectUrl,
+
+    /// The result of the latest successful discovery
+    ///
+    /// This is `None` until the provider could be reached for the first time.
+    discovery: RwLock<Option<Arc<Discovery>>>,
+}
+
+/// The configuration of a provider retrieved through oidc discovery
+struct Discovery {
     /// OIDC client used to construct requests for the issuer.
     ///
     /// This type mostly consists of configuration and logic.
     oidc_client: OidcClient,
+
+    /// The issuer's endpoint for RP-initiated logout, if it supports it
+    end_session_endpoint: Option<EndSessionUrl>,
+
+    /// The point in time the discovery happened
+    discovered_at: Instant,
 }
 
-/// Type alias for the highly generic [`CoreClient`] type.
-type OidcClient = CoreClient<
+/// Type alias for the highly generic [`Client`] type.
+///
+/// It matches [`CoreClient`](openidconnect::core::CoreClient) except for the [`ExtraClaims`].
+type OidcClient = Client<
+    ExtraClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    StandardTokenResponse<
+        IdTokenFields<
+            ExtraClaims,
+            EmptyExtraTokenFields,
+            CoreGenderClaim,
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+        >,
+        CoreTokenType,
+    >,
+    CoreTokenIntrospectionResponse,
+    CoreRevocableToken,
+    CoreRevocationErrorResponse,
     EndpointSet, // Auth URL
     EndpointNotSet,
     EndpointNotSet,
@@ -66,9 +168,54 @@ type OidcClient = CoreClient<
     EndpointMaybeSet,
 >;
 
+/// An id token issued by the oidc provider
+pub type OidcIdToken = IdToken<
+    ExtraClaims,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+>;
+
+/// The claims of an id token issued by the oidc provider
+pub type OidcIdTokenClaims = IdTokenClaims<ExtraClaims, CoreGenderClaim>;
+
+/// Claims of an id token which are not defined by the OpenID Connect standard (e.g. `groups`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ExtraClaims(pub HashMap<String, serde_json::Value>);
+impl AdditionalClaims for ExtraClaims {}
+
+/// The state required to log a user out at the issuer which is stored in the user's session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcLogoutState {
+    /// Name of the provider the user logged in with.
+    pub provider: String,
+
+    /// The id token issued during the login.
+    ///
+    /// It is passed to the issuer as hint which user to log out.
+    pub id_token: OidcIdToken,
+}
+
+/// The relevant claims of a verified logout token
+#[derive(Debug, Clone)]
+pub struct OidcLogoutClaims {
+    /// The issuer of the logout token
+    pub issuer: String,
+
+    /// The subject to log out
+    pub subject: String,
+
+    /// The session id (`sid`) of the login to end
+    pub sid: Option<String>,
+}
+
 /// The part of the state required during an ongoing oidc authentication which is stored in the user's session.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OidcSessionState {
+    /// Name of the provider the authentication was started with.
+    pub provider: String,
+
     /// Random value which takes a round trip through the issuer to detect tampering.
     pub csrf_token: CsrfToken,
 
@@ -77,8 +224,29 @@ pub struct OidcSessionState {
 
     /// Nonce used to verify the id token's integrity.
     pub nonce: Nonce,
+
+    /// Relative path to redirect the user to after the login has finished.
+    #[serde(default)]
+    pub return_to: Option<String>,
 }
 
+/// A failed login
+///
+/// Next to the error to respond with, it carries the reason of the failure for the login log.
+pub struct OidcLoginError {
+    /// Why the login failed
+    pub reason: &'static str,
+
+    /// The error to respond with
+    pub error: ApiError,
+}
+
+/// Error of an oidc provider which could not be discovered yet
+///
+/// It is responded with `503 Service Unavailable`.
+#[derive(Debug, Copy, Clone)]
+pub struct OidcProviderUnavailable;
+
 /// The part of the state required during an ongoing oidc authentication which is passed in the redirect url.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OidcRequestState {
@@ -90,17 +258,143 @@ pub struct OidcRequestState {
 }
 
 impl OpenIdConnect {
+    /// Retrieves a provider by its name
+    pub fn provider(&self, name: &str) -> ApiResult<&OidcProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name == name)
+            .ok_or(ApiError::bad_request("Unknown oidc provider"))
+    }
+
+    /// Iterates over all configured providers
+    pub fn providers(&self) -> impl Iterator<Item = &OidcProvider> {
+        self.providers.iter()
+    }
+
+    /// Spawns the tasks periodically re-discovering the providers
+    ///
+    /// This picks up rotated signing keys and other changes of the providers' configurations.
+    /// Providers which could not be discovered yet are retried more frequently.
+    pub fn start_refresh_task(&'static self) {
+        let refresh_interval = Duration::from_secs(OIDC_REFRESH_INTERVAL.get().get());
+        for provider in &self.providers {
+            tokio::spawn(async move {
+                loop {
+                    let delay = if provider.is_available() {
+                        refresh_interval
+                    } else {
+                        RETRY_INTERVAL
+                    };
+                    tokio::time::sleep(delay).await;
+                    provider.refresh().await;
+                }
+            });
+        }
+    }
+}
+
+impl OidcProvider {
+    /// Checks whether the provider has been discovered and logins are possible
+    pub fn is_available(&self) -> bool {
+        self.discovery
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Retrieves the result of the latest discovery
+    fn discovery(&self) -> Result<Arc<Discovery>, OidcProviderUnavailable> {
+        self.discovery
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .ok_or(OidcProviderUnavailable)
+    }
+
+    /// Checks an issuer to be the one discovered through the configured url
+    ///
+    /// A trailing slash is ignored, as some providers add it to their issuer and some don't.
+    fn is_issuer(&self, issuer: &IssuerUrl) -> bool {
+        issuer.as_str().trim_end_matches('/')
+            == self.config.discover_url.as_str().trim_end_matches('/')
+    }
+
+    /// Re-discovers the provider's configuration
+    ///
+    /// Failures are logged and the previous discovery is kept.
+    #[instrument(name = "OidcProvider::refresh", skip(self), fields(provider = self.name))]
+    async fn refresh(&self) -> Option<Arc<Discovery>> {
+        match self.config.discover_retry::<3>(&self.http_client).await {
+            Ok(discovery) => {
+                let discovery = Arc::new(discovery);
+                *self
+                    .discovery
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(discovery.clone());
+                debug!("Discovered oidc provider");
+                Some(discovery)
+            }
+            Err(error) => {
+                error!(error.display = %error, error.debug = ?error, "Failed to discover oidc provider");
+                None
+            }
+        }
+    }
+
+    /// Re-discovers the provider after a token was signed by an unknown key
+    ///
+    /// The provider is likely to have rotated its keys.
+    /// To not be abused to flood the provider with requests,
+    /// this does nothing if the last discovery is more recent than [`MIN_KEY_REFRESH_INTERVAL`].
+    async fn refresh_keys(&self, current: &Discovery) -> Option<Arc<Discovery>> {
+        if current.discovered_at.elapsed() < MIN_KEY_REFRESH_INTERVAL {
+            return None;
+        }
+        self.refresh().await
+    }
+
+    /// Verifies an id token, refreshing the provider's keys if it was signed by an unknown one
+    async fn verify_id_token<'t>(
+        &self,
+        discovery: Arc<Discovery>,
+        id_token: &'t OidcIdToken,
+        nonce_verifier: impl NonceVerifier + Clone,
+    ) -> Result<(&'t OidcIdTokenClaims, Arc<Discovery>), ClaimsVerificationError> {
+        let result = id_token.claims(
+            &discovery.oidc_client.id_token_verifier(),
+            nonce_verifier.clone(),
+        );
+        match result {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {
+                let Some(discovery) = self.refresh_keys(&discovery).await else {
+                    return Err(ClaimsVerificationError::SignatureVerification(
+                        SignatureVerificationError::NoMatchingKey,
+                    ));
+                };
+                let claims =
+                    id_token.claims(&discovery.oidc_client.id_token_verifier(), nonce_verifier)?;
+                Ok((claims, discovery))
+            }
+            result => Ok((result?, discovery)),
+        }
+    }
+
     /// Initiates a new oidc authentication.
     ///
     /// # Returns
     /// The url the user should be redirected to
     /// and some state that should be stored in the user's session.
-    pub fn begin_login(&self) -> ApiResult<(Url, OidcSessionState)> {
+    ///
+    /// `return_to` has to be validated by the caller.
+    pub fn begin_login(&self, return_to: Option<String>) -> ApiResult<(Url, OidcSessionState)> {
         // Create a PKCE code verifier and SHA-256 encode it as a code challenge.
         let (pkce_code_challenge, pkce_code_verifier) = PkceCodeChallenge::new_random_sha256();
 
         // Generate the authorization URL to which we'll redirect the user.
-        let request = self
+        let discovery = self.discovery()?;
+        let request = discovery
             .oidc_client
             .authorize_url(
                 CoreAuthenticationFlow::AuthorizationCode,
@@ -108,15 +402,17 @@ impl OpenIdConnect {
                 Nonce::new_random,
             )
             .set_pkce_challenge(pkce_code_challenge)
-            .add_scope(Scope::new("profile".to_string()));
+            .add_scopes(self.config.scopes.iter().cloned());
         let (auth_url, csrf_token, nonce) = request.url();
 
         Ok((
             auth_url,
             OidcSessionState {
+                provider: self.name.clone(),
                 csrf_token,
                 nonce,
                 pkce_code_verifier,
+                return_to,
             },
         ))
     }
@@ -130,21 +426,31 @@ impl OpenIdConnect {
     /// - `request` the state passed from the issuer through the redirect url.
     ///
     /// # Returns
-    /// The claims provided by the issuer.
+    /// The claims provided by the issuer and the id token containing them.
     ///
     /// The caller is responsible for processing them into database models.
+    /// The id token should be kept to be passed as hint to [`Self::logout_url`].
     pub async fn finish_login(
         &self,
         session: OidcSessionState,
         request: OidcRequestState,
-    ) -> ApiResult<CoreIdTokenClaims> {
+    ) -> Result<(OidcIdTokenClaims, OidcIdToken), OidcLoginError> {
         // Check the states to match
         if request.state != session.csrf_token {
-            return Err(ApiError::unauthorized("Secret state is invalid"));
+            return Err(OidcLoginError::unauthorized("Secret state is invalid"));
+        }
+        if session.provider != self.name {
+            return Err(OidcLoginError::bad_request(
+                "The login was started with another provider",
+            ));
         }
 
         // Exchange the authorization code with a token.
-        let token_response = self
+        let discovery = self.discovery().map_err(|error| OidcLoginError {
+            reason: "Oidc provider is currently unavailable",
+            error: error.into(),
+        })?;
+        let token_response = discovery
             .oidc_client
             .exchange_code(request.code)
             .set_pkce_verifier(session.pkce_code_verifier)
@@ -152,42 +458,284 @@ impl OpenIdConnect {
             .await
             .map_err(|error| match error {
                 RequestTokenError::ServerResponse(_) => {
-                    ApiError::server_error("server response invalid")
+                    OidcLoginError::server_error("server response invalid")
                 }
-                _ => ApiError::bad_request("bad request"),
+                _ => OidcLoginError::bad_request("bad request"),
             })?;
 
         // Extract the ID token claims after verifying its authenticity and nonce.
-        let id_token = token_response.id_token().ok_or(ApiError::server_error(
-            "Oidc provider did not provider an id token. \
-            This would suggest its not providing oidc.",
-        ))?;
-        let id_token_verifier = self.oidc_client.id_token_verifier();
-        let claims = id_token
-            .claims(&id_token_verifier, &session.nonce)
-            .map_err(|error| ApiError::unauthorized("Failed to verify id token"))?;
+        let id_token = token_response
+            .id_token()
+            .ok_or(OidcLoginError::server_error(
+                "Oidc provider did not provider an id token. \
+                This would suggest its not providing oidc.",
+            ))?;
+        let (claims, discovery) = self
+            .verify_id_token(discovery, id_token, &session.nonce)
+            .await
+            .map_err(|_| OidcLoginError::unauthorized("Failed to verify id token"))?;
+        let id_token_verifier = discovery.oidc_client.id_token_verifier();
+
+        // Check the issuer to be the configured one
+        if !self.is_issuer(claims.issuer()) {
+            return Err(OidcLoginError::unauthorized(
+                "Id token was issued by an unknown issuer",
+            ));
+        }
 
         // Verify the access token hash to ensure that the access token hasn't been substituted for
         // another user's.
         if let Some(expected_access_token_hash) = claims.access_token_hash() {
             let actual_access_token_hash = AccessTokenHash::from_token(
                 token_response.access_token(),
-                id_token.signing_alg().map_err(ApiError::map_server_error(
-                    "Failed to retrieve signing algorithm",
-                ))?,
                 id_token
-                    .signing_key(&id_token_verifier)
-                    .map_err(ApiError::map_server_error("Failed to retrieve signing key"))?,
+                    .signing_alg()
+                    .map_err(OidcLoginError::map_server_error(
+                        "Failed to retrieve signing algorithm",
+                    ))?,
+                id_token.signing_key(&id_token_verifier).map_err(
+                    OidcLoginError::map_server_error("Failed to retrieve signing key"),
+                )?,
             )
-            .map_err(ApiError::map_server_error(
+            .map_err(OidcLoginError::map_server_error(
                 "Failed to recreate access token signature",
             ))?;
             if actual_access_token_hash != *expected_access_token_hash {
-                return Err(ApiError::unauthorized("Invalid access token"));
+                return Err(OidcLoginError::unauthorized("Invalid access token"));
             }
         }
 
-        Ok(claims.clone())
+        Ok((claims.clone(), id_token.clone()))
+    }
+
+    /// Verifies a logout token sent by the issuer to the back-channel logout endpoint
+    ///
+    /// Logout tokens are verified like id tokens,
+    /// except that they must not contain a nonce and have to contain the back-channel logout event.
+    /// Logout tokens without a `sub` claim are not supported.
+    ///
+    /// # Returns
+    /// The subject to log out
+    /// and the session id (`sid`) if the token restricts the logout to a single login.
+    pub async fn verify_logout_token(&self, logout_token: &str) -> ApiResult<OidcLogoutClaims> {
+        let logout_token = OidcIdToken::from_str(logout_token)
+            .map_err(|_| ApiError::bad_request("Malformed logout token"))?;
+        let (claims, _) =
+            self.verify_id_token(self.discovery()?, &logout_token, |nonce: Option<&Nonce>| {
+                match nonce {
+                    Some(_) => Err("Logout tokens must not contain a nonce".to_string()),
+                    None => Ok(()),
+                }
+            })
+            .await
+            .map_err(|_| ApiError::bad_request("Failed to verify logout token"))?;
+
+        if !self.is_issuer(claims.issuer()) {
+            return Err(ApiError::bad_request(
+                "Logout token was issued by an unknown issuer",
+            ));
+        }
+
+        let extra_claims = &claims.additional_claims().0;
+        let is_logout_event = extra_claims
+            .get("events")
+            .and_then(|events| events.as_object())
+            .is_some_and(|events| events.contains_key(BACKCHANNEL_LOGOUT_EVENT));
+        if !is_logout_event {
+            return Err(ApiError::bad_request(
+                "Logout token does not contain the logout event",
+            ));
+        }
+
+        Ok(OidcLogoutClaims {
+            issuer: claims.issuer().to_string(),
+            subject: claims.subject().to_string(),
+            sid: session_id(claims),
+        })
+    }
+
+    /// Constructs the url to log the user out at the issuer as well
+    ///
+    /// # Returns
+    /// `None` if the issuer does not support RP-initiated logout.
+    /// If the provider is currently unavailable, `None` is returned as well.
+    pub fn logout_url(&self, id_token: &OidcIdToken) -> Option<Url> {
+        let discovery = self.discovery().ok()?;
+        let end_session_endpoint = discovery.end_session_endpoint.clone()?;
+        Some(
+            LogoutRequest::from(end_session_endpoint)
+                .set_id_token_hint(id_token)
+                .set_client_id(discovery.oidc_client.client_id().clone())
+                .set_post_logout_redirect_uri(self.post_logout_redirect_url.clone())
+                .http_get_url(),
+        )
+    }
+
+    /// Applies the role mapping to the claims of a login
+    ///
+    /// Roles which appear in the mapping are managed by the issuer:
+    /// they are granted if the claim contains a matching value and revoked otherwise.
+    /// All other roles are kept.
+    ///
+    /// # Returns
+    /// The account's new roles or `None` if no mapping is configured.
+    pub fn map_roles(
+        &self,
+        claims: &OidcIdTokenClaims,
+        current_roles: Vec<AccountRole>,
+    ) -> Option<Vec<AccountRole>> {
+        let role_mapping = &self.config.role_mapping;
+        if role_mapping.is_empty() {
+            return None;
+        }
+
+        let claimed_values: Vec<&str> =
+            match claims.additional_claims().0.get(&self.config.role_claim) {
+                Some(serde_json::Value::String(value)) => vec![value.as_str()],
+                Some(serde_json::Value::Array(values)) => {
+                    values.iter().filter_map(|value| value.as_str()).collect()
+                }
+                _ => Vec::new(),
+            };
+
+        let mut roles: Vec<AccountRole> = current_roles
+            .into_iter()
+            .filter(|role| !role_mapping.values().any(|managed| managed == role))
+            .collect();
+        roles.extend(
+            claimed_values
+                .into_iter()
+                .filter_map(|value| role_mapping.get(value).copied()),
+        );
+        Some(roles)
+    }
+
+    /// Reads the account's display name from the claims
+    ///
+    /// The configured `display_name_claims` are tried in order and the first one present is used.
+    pub fn display_name(&self, claims: &OidcIdTokenClaims) -> Option<String> {
+        self.config
+            .display_name_claims
+            .iter()
+            .find_map(|claim| self.claim(claims, claim))
+    }
+
+    /// Reads the account's email address from the claims
+    pub fn email(&self, claims: &OidcIdTokenClaims) -> Option<String> {
+        self.claim(claims, &self.config.email_claim)
+    }
+
+    /// Reads a claim as string
+    ///
+    /// Localized claims (e.g. `name#de`) prefer the configured `claim_locales` in order
+    /// and fall back to the claim without a locale.
+    /// Empty values are treated as missing.
+    fn claim(&self, claims: &OidcIdTokenClaims, claim: &str) -> Option<String> {
+        let locales = &self.config.claim_locales;
+        let value = match claim {
+            "sub" => Some(claims.subject().to_string()),
+            "name" => localized(claims.name(), locales),
+            "given_name" => localized(claims.given_name(), locales),
+            "family_name" => localized(claims.family_name(), locales),
+            "nickname" => localized(claims.nickname(), locales),
+            "preferred_username" => claims
+                .preferred_username()
+                .map(|username| username.to_string()),
+            "email" => claims.email().map(|email| email.to_string()),
+            _ => claims
+                .additional_claims()
+                .0
+                .get(claim)
+                .and_then(|value| value.as_str())
+                .map(str::to_string),
+        };
+        value.filter(|value| !value.trim().is_empty())
+    }
+}
+
+/// Picks the value of a localized claim matching the first available locale
+fn localized<T: Deref<Target = String>>(
+    claim: Option<&LocalizedClaim<T>>,
+    locales: &[LanguageTag],
+) -> Option<String> {
+    let claim = claim?;
+    locales
+        .iter()
+        .find_map(|locale| claim.get(Some(locale)))
+        .or_else(|| claim.get(None))
+        .map(|value| value.to_string())
+}
+
+impl OidcLoginError {
+    /// A login which failed because the user could not be authenticated
+    pub fn unauthorized(reason: &'static str) -> Self {
+        Self {
+            reason,
+            error: ApiError::unauthorized(reason),
+        }
+    }
+
+    /// A login which failed because of a malformed request
+    pub fn bad_request(reason: &'static str) -> Self {
+        Self {
+            reason,
+            error: ApiError::bad_request(reason),
+        }
+    }
+
+    /// A login which failed because of an internal error
+    pub fn server_error(reason: &'static str) -> Self {
+        Self {
+            reason,
+            error: ApiError::server_error(reason),
+        }
+    }
+
+    /// Maps an error, which caused a login to fail, into an internal error
+    pub fn map_server_error<E>(reason: &'static str) -> impl FnOnce(E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        move |error| Self {
+            reason,
+            error: ApiError::map_server_error(reason)(error),
+        }
+    }
+}
+
+impl IntoResponse for OidcProviderUnavailable {
+    fn into_response(self) -> Response {
+        let mut response =
+            ApiError::server_error("The oidc provider is currently unavailable").into_response();
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from_static("30"));
+        response
+    }
+}
+
+/// Routes requiring a discovery are wrapped by the
+/// [`OidcProviderAvailableLayer`](crate::http::middlewares::oidc_provider_available::OidcProviderAvailableLayer),
+/// which responds with `503 Service Unavailable` before the handler is reached.
+impl From<OidcProviderUnavailable> for ApiError {
+    fn from(_: OidcProviderUnavailable) -> Self {
+        ApiError::server_error("The oidc provider is currently unavailable")
+    }
+}
+
+impl From<anyhow::Error> for OidcLoginError {
+    fn from(error: anyhow::Error) -> Self {
+        Self {
+            reason: "Internal server error",
+            error: ApiError::from(error),
+        }
+    }
+}
+
+impl From<OidcLoginError> for ApiError {
+    fn from(value: OidcLoginError) -> Self {
+        value.error
     }
 }
 
@@ -201,14 +749,35 @@ impl Module for OpenIdConnect {
             .build()
             .unwrap();
 
-        let oidc_client = OidcConfig::from_env()
-            .discover_retry::<3>(&http_client)
-            .await?;
+        let configs = OidcProviderConfig::from_file(&OIDC_PROVIDERS_FILE)?;
+
+        let mut providers = Vec::with_capacity(configs.len());
+        for config in configs {
+            let post_logout_redirect_url = match &config.post_logout_redirect_url {
+                Some(url) => url.clone(),
+                None => PostLogoutRedirectUrl::from_url(config.redirect_url.url().join("/")?),
+            };
+            let provider = OidcProvider {
+                name: config.name.clone(),
+                ldap: config.ldap,
+                http_client: http_client.clone(),
+                config,
+                post_logout_redirect_url,
+                discovery: RwLock::new(None),
+            };
+
+            // An unreachable provider must not prevent the startup.
+            // The refresh task keeps trying to discover it.
+            if provider.refresh().await.is_none() {
+                warn!(
+                    provider = provider.name,
+                    "Oidc provider is unavailable, logins will fail until it could be discovered"
+                );
+            }
+            providers.push(provider);
+        }
 
-        Ok(Self {
-            http_client,
-            oidc_client,
-        })
+        Ok(Self { providers })
     }
 
     type Dependencies = ();
@@ -218,10 +787,19 @@ impl Module for OpenIdConnect {
     }
 }
 
-/// Config set by admin to connect to the oidc provider.
-struct OidcConfig {
+/// Config set by admin to connect to an oidc provider.
+///
+/// A list of them is read from [`OIDC_PROVIDERS_FILE`].
+///
+/// To support back-channel logouts, the provider has to be configured to
+/// send them to `/api/frontend/v1/oidc/{name}/backchannel-logout`.
+#[derive(Debug, Deserialize)]
+struct OidcProviderConfig {
+    /// Name identifying the provider in urls
+    name: String,
+
     /// The provider's url.
-    url: IssuerUrl,
+    discover_url: IssuerUrl,
 
     /// The client id
     client_id: ClientId,
@@ -230,38 +808,130 @@ struct OidcConfig {
     client_secret: ClientSecret,
 
     /// The url to redirect users to, to finish the authentication.
+    ///
+    /// This should point to `/api/frontend/v1/oidc/{name}/finish-login`.
     redirect_url: RedirectUrl,
+
+    /// The url the issuer should redirect users to after logging them out.
+    ///
+    /// Defaults to the root of the `redirect_url`'s host.
+    #[serde(default)]
+    post_logout_redirect_url: Option<PostLogoutRedirectUrl>,
+
+    /// The scopes to request
+    #[serde(default = "default_scopes")]
+    scopes: Vec<Scope>,
+
+    /// The claim containing the values which are mapped to roles
+    #[serde(default = "default_role_claim")]
+    role_claim: String,
+
+    /// Maps values of the `role_claim` to the roles they grant
+    ///
+    /// Roles appearing in this mapping are granted and revoked on every login.
+    /// If it is empty, roles are not managed by the oidc provider.
+    #[serde(default)]
+    role_mapping: HashMap<String, AccountRole>,
+
+    /// Whether users of this provider have to be part of the LDAP directory
+    #[serde(default = "default_ldap")]
+    ldap: bool,
+
+    /// The claims to read the display name from
+    ///
+    /// They are tried in order and the first one present is used.
+    #[serde(default = "default_display_name_claims")]
+    display_name_claims: Vec<String>,
+
+    /// The claim to read the email address from
+    ///
+    /// Most providers only send the standard `email` claim if the `email` scope is requested.
+    #[serde(default = "default_email_claim")]
+    email_claim: String,
+
+    /// The preferred locales of localized claims like `name#de`
+    ///
+    /// If none of them is present, the claim without a locale is used.
+    #[serde(default)]
+    claim_locales: Vec<LanguageTag>,
+}
+
+/// Only request the `profile` scope by default
+fn default_scopes() -> Vec<Scope> {
+    vec![Scope::new("profile".to_string())]
+}
+
+/// Read roles from the `groups` claim by default
+fn default_role_claim() -> String {
+    "groups".to_string()
+}
+
+/// Require users to be part of the LDAP directory by default
+fn default_ldap() -> bool {
+    true
 }
 
-impl OidcConfig {
-    /// Loads the config from environment variables
-    fn from_env() -> Self {
-        OidcConfig {
-            url: OIDC_DISCOVER_URL.clone(),
-            client_id: OIDC_CLIENT_ID.clone(),
-            client_secret: OIDC_CLIENT_SECRET.clone(),
-            redirect_url: OIDC_REDIRECT_URL.clone(),
+/// Fall back to the username and email address if the provider doesn't send a name
+fn default_display_name_claims() -> Vec<String> {
+    vec![
+        "name".to_string(),
+        "preferred_username".to_string(),
+        "email".to_string(),
+    ]
+}
+
+/// Read the email address from the standard `email` claim by default
+fn default_email_claim() -> String {
+    "email".to_string()
+}
+
+impl OidcProviderConfig {
+    /// Loads the list of providers from a json file
+    fn from_file(path: &str) -> Result<Vec<Self>, InvalidOidcConfig> {
+        let file = std::fs::read_to_string(path)
+            .map_err(|error| InvalidOidcConfig(format!("Failed to read {path}: {error}")))?;
+        let configs: Vec<Self> = serde_json::from_str(&file)
+            .map_err(|error| InvalidOidcConfig(format!("Failed to parse {path}: {error}")))?;
+
+        if configs.is_empty() {
+            return Err(InvalidOidcConfig(format!("{path} contains no providers")));
         }
+        for (index, config) in configs.iter().enumerate() {
+            if configs[..index]
+                .iter()
+                .any(|other| other.name == config.name)
+            {
+                return Err(InvalidOidcConfig(format!(
+                    "{path} contains the provider `{}` twice",
+                    config.name
+                )));
+            }
+        }
+
+        Ok(configs)
     }
 
     /// Tries to discover the provider's configuration `N` times
     async fn discover_retry<const N: usize>(
         &self,
         http_client: &reqwest::Client,
-    ) -> Result<OidcClient, DiscoveryError<HttpClientError<reqwest::Error>>> {
+    ) -> Result<Discovery, DiscoveryError<HttpClientError<reqwest::Error>>> {
         let mut result = Err(DiscoveryError::Other(String::new()));
         for _ in 0..N {
             result = self.discover(http_client).await;
             if let Err(DiscoveryError::Request(HttpClientError::Reqwest(error))) = &result {
                 #[allow(clippy::collapsible_if, reason = "Feature to new")]
                 if error.is_timeout() {
-                    warn!("Timed out fetching oidc discovery, trying again...");
+                    warn!(
+                        provider = self.name,
+                        "Timed out fetching oidc discovery, trying again..."
+                    );
                     continue;
                 }
             }
             return result;
         }
-        error!("Timed out fetching oidc discovery");
+        error!(provider = self.name, "Timed out fetching oidc discovery");
         result
     }
 
@@ -269,9 +939,17 @@ impl OidcConfig {
     async fn discover(
         &self,
         http_client: &reqwest::Client,
-    ) -> Result<OidcClient, DiscoveryError<HttpClientError<reqwest::Error>>> {
-        let oidc_client = CoreClient::from_provider_metadata(
-            CoreProviderMetadata::discover_async(self.url.clone(), http_client).await?,
+    ) -> Result<Discovery, DiscoveryError<HttpClientError<reqwest::Error>>> {
+        let provider_metadata =
+            ProviderMetadataWithLogout::discover_async(self.discover_url.clone(), http_client)
+                .await?;
+        let end_session_endpoint = provider_metadata
+            .additional_metadata()
+            .end_session_endpoint
+            .clone();
+
+        let oidc_client = Client::from_provider_metadata(
+            provider_metadata,
             self.client_id.clone(),
             Some(self.client_secret.clone()),
         )
@@ -284,6 +962,32 @@ impl OidcConfig {
             .clone();
         let oidc_client = oidc_client.set_token_uri(token_uri);
 
-        Ok(oidc_client)
+        Ok(Discovery {
+            oidc_client,
+            end_session_endpoint,
+            discovered_at: Instant::now(),
+        })
     }
 }
+
+/// The file configuring the oidc providers is invalid
+#[derive(Debug)]
+struct InvalidOidcConfig(String);
+
+impl fmt::Display for InvalidOidcConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidOidcConfig {}
+
+/// Extracts the session id (`sid`) the issuer assigned to a login
+pub fn session_id(claims: &OidcIdTokenClaims) -> Option<String> {
+    claims
+        .additional_claims()
+        .0
+        .get("sid")
+        .and_then(|sid| sid.as_str())
+        .map(str::to_string)
+}
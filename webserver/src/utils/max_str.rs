@@ -0,0 +1,14 @@
+//! Helpers for storing arbitrary strings in length limited columns
+
+use galvyn::rorm::fields::types::MaxStr;
+
+/// Cuts off a string to fit into a [`MaxStr`]
+///
+/// The string is cut at a char boundary, so it may end up shorter than `N` bytes.
+pub fn truncate<const N: usize>(value: &str) -> anyhow::Result<MaxStr<N>> {
+    let mut end = value.len().min(N);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    Ok(MaxStr::new(value[..end].to_string())?)
+}
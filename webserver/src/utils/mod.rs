@@ -1,2 +1,6 @@
 //! Utility modules that may be used throughout multiple handlers or from a task
 //! within the webserver are defined here
+
+pub mod max_str;
+pub mod retention;
+pub mod timezone;
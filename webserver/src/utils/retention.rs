@@ -0,0 +1,44 @@
+//! Periodic deletion of records which exceeded their retention period
+
+use galvyn::core::Module;
+use galvyn::rorm::Database;
+use time::OffsetDateTime;
+use tracing::debug;
+use tracing::error;
+use tracing::instrument;
+
+use crate::config::LOGIN_LOG_RETENTION_DAYS;
+use crate::models::account_sessions::AccountSession;
+use crate::models::login_attempts::LoginAttempt;
+
+/// Interval between two deletions
+const INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Spawns a task periodically deleting records which exceeded their retention period
+pub fn start_retention_task() {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(error) = delete_expired().await {
+                error!(error.display = %error, error.debug = ?error, "Deleting expired records failed");
+            }
+        }
+    });
+}
+
+/// Deletes the login attempts older than [`LOGIN_LOG_RETENTION_DAYS`] and the expired sessions
+#[instrument(name = "retention::delete_expired")]
+async fn delete_expired() -> anyhow::Result<()> {
+    let deleted = AccountSession::delete_expired(Database::global()).await?;
+    debug!(deleted, "Deleted expired sessions");
+
+    let retention = time::Duration::days(i64::from(*LOGIN_LOG_RETENTION_DAYS));
+    let Some(cutoff) = OffsetDateTime::now_utc().checked_sub(retention) else {
+        return Ok(());
+    };
+
+    let deleted = LoginAttempt::delete_older_than(Database::global(), cutoff).await?;
+    debug!(deleted, "Deleted expired login attempts");
+    Ok(())
+}
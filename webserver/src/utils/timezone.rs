@@ -0,0 +1,164 @@
+//! The timezone the community lives in
+//!
+//! Dinners only carry a date, so deciding which day is "today"
+//! (or which week is the current one) depends on this timezone instead of the client's.
+
+use std::fmt;
+use std::str::FromStr;
+
+use time::Date;
+use time::Duration;
+use time::OffsetDateTime;
+use time::PrimitiveDateTime;
+use time::Time;
+use time_tz::Offset;
+use time_tz::OffsetDateTimeExt;
+use time_tz::PrimitiveDateTimeExt;
+use time_tz::TimeZone;
+use time_tz::Tz;
+use time_tz::timezones;
+
+/// A timezone from the IANA database (e.g. `Europe/Berlin`)
+#[derive(Debug, Copy, Clone)]
+pub struct Timezone(pub &'static Tz);
+
+impl Timezone {
+    /// The current point in time in this timezone
+    pub fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc().to_timezone(self.0)
+    }
+
+    /// The current day in this timezone
+    pub fn today(&self) -> Date {
+        self.now().date()
+    }
+
+    /// The point in time a wall clock in this timezone shows a time on a day
+    ///
+    /// If the clock shows the time twice (i.e. when switching from daylight saving time),
+    /// the earlier point is returned.
+    /// If it skips the time, the time is interpreted using the offset before the switch.
+    pub fn at(&self, date: Date, time: Time) -> OffsetDateTime {
+        let local = PrimitiveDateTime::new(date, time);
+        local
+            .assume_timezone(self.0)
+            .take_first()
+            .unwrap_or_else(|| {
+                // Clocks are switched at most once a day, so a day earlier the old offset applies
+                let before_switch = self.0.get_offset_utc(&(local - Duration::DAY).assume_utc());
+                local.assume_offset(before_switch.to_utc())
+            })
+    }
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Self(timezones::db::europe::BERLIN)
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = UnknownTimezone;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        timezones::get_by_name(name)
+            .map(Self)
+            .ok_or_else(|| UnknownTimezone(name.to_string()))
+    }
+}
+
+/// A time of the day in the format `HH:MM` (e.g. `10:00`)
+#[derive(Debug, Copy, Clone)]
+pub struct TimeOfDay(pub Time);
+
+impl FromStr for TimeOfDay {
+    type Err = InvalidTimeOfDay;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidTimeOfDay(value.to_string());
+        let (hour, minute) = value.split_once(':').ok_or_else(invalid)?;
+        let hour = hour.parse().map_err(|_| invalid())?;
+        let minute = minute.parse().map_err(|_| invalid())?;
+        Time::from_hms(hour, minute, 0)
+            .map(Self)
+            .map_err(|_| invalid())
+    }
+}
+
+/// A time of the day is not in the format `HH:MM`
+#[derive(Debug, Clone)]
+pub struct InvalidTimeOfDay(pub String);
+
+impl fmt::Display for InvalidTimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid time of the day, expected HH:MM: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidTimeOfDay {}
+
+/// The name of a timezone is not part of the IANA database
+#[derive(Debug, Clone)]
+pub struct UnknownTimezone(pub String);
+
+impl fmt::Display for UnknownTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown timezone: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTimezone {}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use time::Date;
+    use time::Month;
+    use time::OffsetDateTime;
+    use time::PrimitiveDateTime;
+    use time::Time;
+    use time::UtcOffset;
+
+    use super::Timezone;
+
+    /// Berlin's wall clock at `hour:30` on a day of 2025
+    fn berlin_at(month: Month, day: u8, hour: u8) -> OffsetDateTime {
+        Timezone::default().at(
+            Date::from_calendar_date(2025, month, day).unwrap(),
+            Time::from_hms(hour, 30, 0).unwrap(),
+        )
+    }
+
+    /// The expected point in time of [`berlin_at`]
+    fn expected(month: Month, day: u8, hour: u8, offset: i8) -> OffsetDateTime {
+        PrimitiveDateTime::new(
+            Date::from_calendar_date(2025, month, day).unwrap(),
+            Time::from_hms(hour, 30, 0).unwrap(),
+        )
+        .assume_offset(UtcOffset::from_hms(offset, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn at_uses_offset_of_unambiguous_time() {
+        assert_eq!(
+            berlin_at(Month::June, 1, 10),
+            expected(Month::June, 1, 10, 2)
+        );
+    }
+
+    #[test]
+    fn at_uses_earlier_point_of_repeated_time() {
+        assert_eq!(
+            berlin_at(Month::October, 26, 2),
+            expected(Month::October, 26, 2, 2)
+        );
+    }
+
+    #[test]
+    fn at_uses_offset_before_switch_for_skipped_time() {
+        assert_eq!(
+            berlin_at(Month::March, 30, 2),
+            expected(Month::March, 30, 2, 1)
+        );
+    }
+}